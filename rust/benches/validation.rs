@@ -0,0 +1,119 @@
+//! Validation throughput benchmarks.
+//!
+//! Run with `cargo bench --bench validation`. Before benchmarking, the average
+//! number of heap allocations per `validate_data` call on valid data is printed,
+//! so allocation regressions show up alongside timing changes.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use pacts::Validator;
+use serde_json::{json, Value};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const OBJECT_COUNT: usize = 10_000;
+
+fn inventory_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["slot", "material", "amount"],
+        "properties": {
+            "slot": {"type": "integer"},
+            "material": {"type": "string", "pattern": "^[A-Z][a-z]+$"},
+            "amount": {"type": "integer"},
+            "rarity": {"enum": ["common", "rare", "epic"]},
+            "lore": {"type": "array", "items": {"type": "string"}},
+            "enchantments": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["id", "level"],
+                    "properties": {
+                        "id": {"type": "string"},
+                        "level": {"type": "integer"}
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn inventory_items() -> Vec<Value> {
+    (0..OBJECT_COUNT)
+        .map(|i| {
+            let material = ["Paper", "Stone", "Diamond"][i % 3];
+            let rarity = ["common", "rare", "epic"][i % 3];
+            json!({
+                "slot": i % 36,
+                "material": material,
+                "amount": i % 64 + 1,
+                "rarity": rarity,
+                "lore": ["Crafted", "Soulbound"],
+                "enchantments": [{"id": "sharpness", "level": i % 5 + 1}]
+            })
+        })
+        .collect()
+}
+
+fn allocations_per_call(validator: &Validator, items: &[Value], schema: &Value) -> f64 {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for item in items {
+        black_box(validator.validate_data(item, schema));
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    allocations as f64 / items.len() as f64
+}
+
+fn bench_validate_data(c: &mut Criterion) {
+    let validator = Validator::standalone();
+    let schema = inventory_schema();
+    let items = inventory_items();
+
+    assert!(items
+        .iter()
+        .all(|item| validator.validate_data(item, &schema).is_valid()));
+    println!(
+        "validate_data: {:.2} allocations per call on valid data",
+        allocations_per_call(&validator, &items, &schema)
+    );
+
+    let mut group = c.benchmark_group("validation");
+    group.throughput(Throughput::Elements(OBJECT_COUNT as u64));
+    group.bench_function("validate_data", |b| {
+        b.iter(|| {
+            for item in &items {
+                black_box(validator.validate_data(black_box(item), &schema));
+            }
+        })
+    });
+
+    let compiled = validator.compile(&schema).unwrap();
+    group.bench_function("validate_compiled", |b| {
+        b.iter(|| {
+            for item in &items {
+                black_box(validator.validate_compiled(black_box(item), &compiled));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_validate_data);
+criterion_main!(benches);
@@ -4,7 +4,12 @@ use std::path::Path;
 
 fn main() {
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
-    let source_file = Path::new(&manifest_dir).join("resources/sources.yaml");
+    // Forks can embed their own schema sources with PACTS_SOURCES_FILE, resolved
+    // relative to the crate root
+    println!("cargo:rerun-if-env-changed=PACTS_SOURCES_FILE");
+    let source_file = Path::new(&manifest_dir).join(
+        env::var("PACTS_SOURCES_FILE").unwrap_or_else(|_| "resources/sources.yaml".to_string()),
+    );
 
     let out_dir = env::var("OUT_DIR").expect("OUT_DIR not set");
     let dest_file = Path::new(&out_dir).join("sources.yaml");
@@ -12,10 +17,21 @@ fn main() {
     if source_file.exists() {
         fs::copy(&source_file, &dest_file).expect("Failed to copy sources.yaml");
         println!("cargo:rerun-if-changed={}", source_file.display());
+
+        // An explicit SCHEMA_VERSION wins; otherwise use the release tag of the first source
+        println!("cargo:rerun-if-env-changed=SCHEMA_VERSION");
+        let schema_version = env::var("SCHEMA_VERSION").ok().or_else(|| {
+            let sources = fs::read_to_string(&source_file).ok()?;
+            sources
+                .split("/releases/download/")
+                .nth(1)
+                .and_then(|rest| rest.split('/').next())
+                .map(|tag| tag.to_string())
+        });
+        if let Some(schema_version) = schema_version {
+            println!("cargo:rustc-env=SCHEMA_VERSION={}", schema_version);
+        }
     } else {
-        panic!(
-            "sources.yaml not found in resources folder: {}",
-            source_file.display()
-        );
+        panic!("sources.yaml not found: {}", source_file.display());
     }
 }
@@ -0,0 +1,120 @@
+use serde_json::Value;
+use std::io::{BufReader, Bytes, Read};
+
+/// Reads the elements of a top-level JSON array one at a time, holding only the
+/// current element in memory.
+///
+/// An element that is not valid JSON yields an error and reading continues with
+/// the next element. A document that is not an array, or that ends or fails to
+/// read before the array is closed, yields an error and ends the iteration.
+pub(crate) struct ArrayElements<R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    buffer: Vec<u8>,
+    state: State,
+}
+
+#[derive(PartialEq)]
+enum State {
+    Start,
+    First,
+    Rest,
+    Done,
+}
+
+impl<R: Read> ArrayElements<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            bytes: BufReader::new(reader).bytes(),
+            buffer: Vec::new(),
+            state: State::Start,
+        }
+    }
+
+    fn fail(&mut self, message: String) -> Option<Result<Value, String>> {
+        self.state = State::Done;
+        Some(Err(message))
+    }
+
+    fn open_array(&mut self) -> Result<(), String> {
+        loop {
+            match self.bytes.next() {
+                Some(Ok(byte)) if byte.is_ascii_whitespace() => continue,
+                Some(Ok(b'[')) => return Ok(()),
+                Some(Ok(_)) | None => return Err("Expected a top-level JSON array".to_string()),
+                Some(Err(e)) => return Err(format!("Failed to read stream: {}", e)),
+            }
+        }
+    }
+
+    /// Reads bytes into the buffer up to the `,` or `]` that ends the current
+    /// element, returning that delimiter.
+    fn read_element(&mut self) -> Result<u8, String> {
+        self.buffer.clear();
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+
+        loop {
+            let byte = match self.bytes.next() {
+                Some(Ok(byte)) => byte,
+                Some(Err(e)) => return Err(format!("Failed to read stream: {}", e)),
+                None => return Err("Unexpected end of array".to_string()),
+            };
+
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+            } else {
+                match byte {
+                    b'"' => in_string = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' if depth > 0 => depth -= 1,
+                    b',' | b']' if depth == 0 => return Ok(byte),
+                    _ => {}
+                }
+            }
+            self.buffer.push(byte);
+        }
+    }
+}
+
+impl<R: Read> Iterator for ArrayElements<R> {
+    type Item = Result<Value, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.state {
+            State::Done => return None,
+            State::Start => {
+                if let Err(e) = self.open_array() {
+                    return self.fail(e);
+                }
+                self.state = State::First;
+            }
+            State::First | State::Rest => {}
+        }
+
+        let delimiter = match self.read_element() {
+            Ok(delimiter) => delimiter,
+            Err(e) => return self.fail(e),
+        };
+        let first = self.state == State::First;
+        self.state = if delimiter == b']' {
+            State::Done
+        } else {
+            State::Rest
+        };
+
+        if self.buffer.iter().all(|byte| byte.is_ascii_whitespace()) {
+            if first && delimiter == b']' {
+                return None;
+            }
+            return Some(Err("Empty array element".to_string()));
+        }
+
+        Some(serde_json::from_slice(&self.buffer).map_err(|e| format!("Invalid JSON: {}", e)))
+    }
+}
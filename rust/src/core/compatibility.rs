@@ -0,0 +1,115 @@
+use crate::core::validator::sorted_properties;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A change between two versions of a schema that can make data valid under
+/// the old schema invalid under the new one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Incompatibility {
+    /// JSON pointer into the new schema where the breaking change was found.
+    pub path: String,
+    /// Human-readable description of the change.
+    pub description: String,
+}
+
+/// Compares two schemas, recursing into properties and items present in both.
+pub(crate) fn compare(old: &Value, new: &Value) -> Vec<Incompatibility> {
+    let mut incompatibilities = Vec::new();
+    compare_at("", old, new, &mut incompatibilities);
+    incompatibilities
+}
+
+fn compare_at(path: &str, old: &Value, new: &Value, found: &mut Vec<Incompatibility>) {
+    let mut report = |keyword: &str, description: String| {
+        found.push(Incompatibility {
+            path: format!("{}/{}", path, keyword),
+            description,
+        });
+    };
+
+    if let Some(new_types) = types(new) {
+        match types(old) {
+            None => report(
+                "type",
+                format!("Type restricted to {}", new_types.join(", ")),
+            ),
+            Some(old_types) if !old_types.iter().all(|t| type_allowed(t, &new_types)) => report(
+                "type",
+                format!(
+                    "Type narrowed from {} to {}",
+                    old_types.join(", "),
+                    new_types.join(", ")
+                ),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    if let Some(new_enum) = new.get("enum").and_then(|e| e.as_array()) {
+        match old.get("enum").and_then(|e| e.as_array()) {
+            None => report("enum", "Enum added".to_string()),
+            Some(old_enum) => {
+                for value in old_enum.iter().filter(|value| !new_enum.contains(value)) {
+                    report("enum", format!("Enum value removed: {}", value));
+                }
+            }
+        }
+    }
+
+    let old_required = string_array(old.get("required"));
+    for field in string_array(new.get("required")) {
+        if !old_required.contains(&field) {
+            report("required", format!("New required field: {}", field));
+        }
+    }
+
+    let new_properties = new.get("properties").and_then(|p| p.as_object());
+    if let Some(old_properties) = old.get("properties").and_then(|p| p.as_object()) {
+        for (name, old_property) in sorted_properties(old_properties) {
+            let property_path = format!("{}/properties/{}", path, escape_pointer_token(name));
+            match new_properties.and_then(|properties| properties.get(name)) {
+                Some(new_property) => compare_at(&property_path, old_property, new_property, found),
+                None => found.push(Incompatibility {
+                    path: property_path,
+                    description: format!("Property removed: {}", name),
+                }),
+            }
+        }
+    }
+
+    if let (Some(old_items), Some(new_items)) = (old.get("items"), new.get("items")) {
+        if old_items.is_object() && new_items.is_object() {
+            compare_at(&format!("{}/items", path), old_items, new_items, found);
+        }
+    }
+}
+
+/// Gets the types a schema allows, or `None` if it allows any type.
+fn types(schema: &Value) -> Option<Vec<&str>> {
+    match schema.get("type")? {
+        Value::String(t) => Some(vec![t.as_str()]),
+        Value::Array(ts) => Some(ts.iter().filter_map(|t| t.as_str()).collect()),
+        _ => None,
+    }
+}
+
+fn type_allowed(old_type: &str, new_types: &[&str]) -> bool {
+    new_types.contains(&old_type) || (old_type == "integer" && new_types.contains(&"number"))
+}
+
+fn string_array(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Escapes `~` and `/` in a JSON pointer token.
+pub(crate) fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
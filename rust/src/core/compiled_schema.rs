@@ -0,0 +1,279 @@
+use crate::core::validator::{location_label, sorted_properties};
+use crate::ValidationError;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A schema pre-processed for repeated validation.
+///
+/// Required fields, the expected type, and property and item schemas are
+/// extracted and patterns are compiled once, so
+/// [`crate::Validator::validate_compiled`] does not have to walk the schema
+/// `Value` or look up regexes on every call. Each `$ref` target is compiled
+/// once and shared by every reference to it, so recursive schemas compile to a
+/// finite graph.
+#[derive(Debug, Clone, Default)]
+pub struct CompiledSchema {
+    pub(crate) schema_type: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) required: Vec<String>,
+    pub(crate) enum_values: Option<Vec<Value>>,
+    pub(crate) pattern: Option<Arc<Regex>>,
+    pub(crate) min_length: Option<u64>,
+    pub(crate) max_length: Option<u64>,
+    pub(crate) custom_rule: Option<String>,
+    pub(crate) read_only: bool,
+    pub(crate) write_only: bool,
+    pub(crate) properties: Vec<(String, CompiledSchema)>,
+    pub(crate) prefix_items: Vec<CompiledSchema>,
+    pub(crate) items: CompiledItems,
+    /// Set on a `$ref` node: the index of its target in the root's `refs`.
+    pub(crate) ref_index: Option<usize>,
+    /// The compiled `$ref` targets, held by the root schema only.
+    pub(crate) refs: Vec<CompiledSchema>,
+}
+
+/// How array elements beyond `prefixItems` are validated.
+#[derive(Debug, Clone, Default)]
+pub(crate) enum CompiledItems {
+    #[default]
+    Any,
+    Forbidden,
+    Schema(Box<CompiledSchema>),
+}
+
+/// Fetches the document an absolute `$ref` URL points to.
+pub(crate) type FetchDocument<'a> = dyn Fn(&str) -> Result<Arc<Value>, String> + 'a;
+
+impl CompiledSchema {
+    /// Compiles a schema `Value`, failing if any `pattern` is not a valid
+    /// regular expression or a fragment `$ref` does not resolve. Schemas with
+    /// external `$ref`s must be compiled with [`crate::Validator::compile`].
+    pub fn new(schema: &Value) -> Result<Self, ValidationError> {
+        Self::compile_with(schema, &|url| {
+            Err(format!(
+                "External $ref requires Validator::compile: {}",
+                url
+            ))
+        })
+    }
+
+    /// Compiles a schema `Value`, fetching external `$ref` documents with `fetch`.
+    pub(crate) fn compile_with(
+        schema: &Value,
+        fetch: &FetchDocument<'_>,
+    ) -> Result<Self, ValidationError> {
+        let mut compiler = Compiler {
+            fetch,
+            refs: Vec::new(),
+            ref_indexes: HashMap::new(),
+        };
+        let mut compiled = compiler.compile_node(schema, schema, "", None)?;
+        compiler.check_ref_cycles()?;
+        compiled.refs = compiler.refs;
+        Ok(compiled)
+    }
+
+    /// Follows `$ref` nodes to the schema they stand for. `refs` are the root
+    /// schema's compiled targets.
+    pub(crate) fn resolve<'a>(&'a self, refs: &'a [CompiledSchema]) -> &'a CompiledSchema {
+        let mut node = self;
+        while let Some(index) = node.ref_index {
+            node = &refs[index];
+        }
+        node
+    }
+
+    /// Returns the expected type, if the schema declares one.
+    pub fn schema_type(&self) -> Option<&str> {
+        self.schema_type.as_deref()
+    }
+
+    /// Returns the required field names.
+    pub fn required(&self) -> &[String] {
+        &self.required
+    }
+}
+
+/// Compiles a schema, collecting each `$ref` target once.
+struct Compiler<'a> {
+    fetch: &'a FetchDocument<'a>,
+    refs: Vec<CompiledSchema>,
+    ref_indexes: HashMap<String, usize>,
+}
+
+impl Compiler<'_> {
+    /// Compiles a `$ref` to a node pointing at its target, compiling the target
+    /// the first time it is referenced. `root` is the document fragment
+    /// references resolve against, fetched from `base` (empty for the schema
+    /// being compiled).
+    fn compile_ref(
+        &mut self,
+        reference: &str,
+        root: &Value,
+        base: &str,
+        field: Option<&str>,
+    ) -> Result<CompiledSchema, ValidationError> {
+        let (url, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let base = if url.is_empty() { base } else { url };
+        let key = format!("{}#{}", base, fragment);
+        let index = match self.ref_indexes.get(&key) {
+            Some(&index) => index,
+            None => {
+                let index = self.refs.len();
+                self.refs.push(CompiledSchema::default());
+                self.ref_indexes.insert(key, index);
+
+                let unresolved = |message: String| {
+                    ValidationError::new(ValidationError::REF_UNRESOLVED, message)
+                };
+                let document;
+                let root = if url.is_empty() {
+                    root
+                } else {
+                    document = (self.fetch)(url).map_err(unresolved)?;
+                    &*document
+                };
+                let target = root
+                    .pointer(fragment)
+                    .ok_or_else(|| unresolved(format!("Unresolved $ref: {}", reference)))?;
+                self.refs[index] = self.compile_node(target, root, base, field)?;
+                index
+            }
+        };
+
+        Ok(CompiledSchema {
+            ref_index: Some(index),
+            ..CompiledSchema::default()
+        })
+    }
+
+    /// Rejects `$ref`s that only lead to other `$ref`s in a loop, which would
+    /// never reach a schema to validate against.
+    fn check_ref_cycles(&self) -> Result<(), ValidationError> {
+        let mut references: Vec<(&String, &usize)> = self.ref_indexes.iter().collect();
+        references.sort_by_key(|(_, &index)| index);
+        for (reference, &start) in references {
+            let mut node = &self.refs[start];
+            for _ in 0..=self.refs.len() {
+                match node.ref_index {
+                    Some(index) => node = &self.refs[index],
+                    None => break,
+                }
+            }
+            if node.ref_index.is_some() {
+                return Err(ValidationError::new(
+                    ValidationError::REF_UNRESOLVED,
+                    format!("$ref nesting too deep at: {}", reference),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Compiles a schema node. `field` names the property the node belongs to,
+    /// for error messages; item schemas inherit their array's field.
+    fn compile_node(
+        &mut self,
+        schema: &Value,
+        root: &Value,
+        base: &str,
+        field: Option<&str>,
+    ) -> Result<CompiledSchema, ValidationError> {
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            return self.compile_ref(reference, root, base, field);
+        }
+
+        let schema_type = schema
+            .get("type")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string());
+
+        let required = schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter_map(|field| field.as_str())
+                    .map(|field| field.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let properties = schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .map(|properties_obj| {
+                sorted_properties(properties_obj)
+                    .map(|(name, property_schema)| {
+                        Ok((
+                            name.clone(),
+                            self.compile_node(property_schema, root, base, Some(name))?,
+                        ))
+                    })
+                    .collect::<Result<_, ValidationError>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let prefix_items = schema
+            .get("prefixItems")
+            .and_then(|p| p.as_array())
+            .map(|item_schemas| {
+                item_schemas
+                    .iter()
+                    .map(|item_schema| self.compile_node(item_schema, root, base, field))
+                    .collect::<Result<_, ValidationError>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let items = match schema.get("items") {
+            Some(Value::Bool(false)) => CompiledItems::Forbidden,
+            Some(item_schema) if item_schema.is_object() => CompiledItems::Schema(Box::new(
+                self.compile_node(item_schema, root, base, field)?,
+            )),
+            _ => CompiledItems::Any,
+        };
+
+        let pattern = match schema.get("pattern").and_then(|p| p.as_str()) {
+            Some(pattern) => Some(Arc::new(Regex::new(pattern).map_err(|_| {
+                ValidationError::new(
+                    ValidationError::PATTERN_INVALID,
+                    format!(
+                        "Invalid pattern{}: {}",
+                        location_label(field, None),
+                        pattern
+                    ),
+                )
+            })?)),
+            None => None,
+        };
+
+        Ok(CompiledSchema {
+            schema_type,
+            description: schema
+                .get("description")
+                .and_then(|d| d.as_str())
+                .map(|d| d.to_string()),
+            required,
+            enum_values: schema.get("enum").and_then(|e| e.as_array()).cloned(),
+            pattern,
+            min_length: schema.get("minLength").and_then(|n| n.as_u64()),
+            max_length: schema.get("maxLength").and_then(|n| n.as_u64()),
+            custom_rule: schema
+                .get("x-custom")
+                .and_then(|r| r.as_str())
+                .map(|r| r.to_string()),
+            read_only: schema.get("readOnly").and_then(|f| f.as_bool()) == Some(true),
+            write_only: schema.get("writeOnly").and_then(|f| f.as_bool()) == Some(true),
+            properties,
+            prefix_items,
+            items,
+            ref_index: None,
+            refs: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,31 @@
+use ring::digest::{digest, SHA256};
+use serde_json::Value;
+
+/// Returns the lowercase hex SHA-256 digest of a schema's JSON serialization,
+/// with object keys sorted so the digest does not depend on key order.
+pub fn schema_digest(schema: &Value) -> String {
+    let bytes = serde_json::to_vec(&sort_keys(schema.clone())).unwrap_or_default();
+    digest(&SHA256, &bytes)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Rebuilds every object in a value with its keys in sorted order.
+pub(crate) fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<(String, Value)> = object.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(elements) => Value::Array(elements.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
@@ -0,0 +1,167 @@
+use crate::core::validator::ValidationResult;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Error returned when a schema cannot be loaded.
+#[derive(Debug)]
+pub enum SchemaLoadError {
+    /// No schema exists for the requested key.
+    NotFound(String),
+    /// Reading the schema failed.
+    Io(io::Error),
+    /// The schema is not valid JSON.
+    InvalidJson(serde_json::Error),
+    /// The configured schema sources could not be read.
+    Source(String),
+    /// A schema id is not of the form `domain/version/category/name`.
+    InvalidId(String),
+    /// The schema is structurally malformed.
+    InvalidSchema(String),
+    /// The schema text exceeds the loader's size or nesting limits.
+    LimitExceeded(String),
+}
+
+impl fmt::Display for SchemaLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaLoadError::NotFound(key) => write!(f, "Schema not found: {}", key),
+            SchemaLoadError::Io(e) => write!(f, "Failed to read schema: {}", e),
+            SchemaLoadError::InvalidJson(e) => write!(f, "Invalid schema JSON: {}", e),
+            SchemaLoadError::Source(message) => write!(f, "Schema source error: {}", message),
+            SchemaLoadError::InvalidId(id) => write!(
+                f,
+                "Invalid schema id '{}': expected domain/version/category/name",
+                id
+            ),
+            SchemaLoadError::InvalidSchema(message) => write!(f, "Malformed schema {}", message),
+            SchemaLoadError::LimitExceeded(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for SchemaLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SchemaLoadError::Io(e) => Some(e),
+            SchemaLoadError::InvalidJson(e) => Some(e),
+            SchemaLoadError::NotFound(_)
+            | SchemaLoadError::Source(_)
+            | SchemaLoadError::InvalidId(_)
+            | SchemaLoadError::InvalidSchema(_)
+            | SchemaLoadError::LimitExceeded(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for SchemaLoadError {
+    fn from(e: io::Error) -> Self {
+        SchemaLoadError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SchemaLoadError {
+    fn from(e: serde_json::Error) -> Self {
+        SchemaLoadError::InvalidJson(e)
+    }
+}
+
+/// Error returned when a schema or data file cannot be used for validation.
+#[derive(Debug)]
+pub enum FileValidationError {
+    /// The schema file could not be read.
+    SchemaUnreadable { path: PathBuf, source: io::Error },
+    /// The schema file is not valid JSON.
+    SchemaInvalid {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// The data file could not be read.
+    DataUnreadable { path: PathBuf, source: io::Error },
+    /// The data file is not valid JSON.
+    DataInvalid {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for FileValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileValidationError::SchemaUnreadable { path, source } => {
+                write!(
+                    f,
+                    "Schema file {} is unreadable: {}",
+                    path.display(),
+                    source
+                )
+            }
+            FileValidationError::SchemaInvalid { path, source } => {
+                write!(
+                    f,
+                    "Schema file {} is not valid JSON: {}",
+                    path.display(),
+                    source
+                )
+            }
+            FileValidationError::DataUnreadable { path, source } => {
+                write!(f, "Data file {} is unreadable: {}", path.display(), source)
+            }
+            FileValidationError::DataInvalid { path, source } => {
+                write!(
+                    f,
+                    "Data file {} is not valid JSON: {}",
+                    path.display(),
+                    source
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FileValidationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FileValidationError::SchemaUnreadable { source, .. }
+            | FileValidationError::DataUnreadable { source, .. } => Some(source),
+            FileValidationError::SchemaInvalid { source, .. }
+            | FileValidationError::DataInvalid { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Error returned by [`crate::PactsService`] operations that validate before
+/// producing output.
+#[derive(Debug)]
+pub enum PactsError {
+    /// The envelope failed validation.
+    Validation(Box<ValidationResult>),
+    /// The envelope could not be serialized.
+    Serialization(serde_json::Error),
+}
+
+impl fmt::Display for PactsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PactsError::Validation(result) => {
+                write!(f, "Validation failed: {}", result.error_message())
+            }
+            PactsError::Serialization(e) => write!(f, "Failed to serialize envelope: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PactsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PactsError::Serialization(e) => Some(e),
+            PactsError::Validation(_) => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for PactsError {
+    fn from(e: serde_json::Error) -> Self {
+        PactsError::Serialization(e)
+    }
+}
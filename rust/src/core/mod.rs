@@ -1,2 +1,11 @@
+pub(crate) mod array_stream;
+pub mod compatibility;
+pub mod compiled_schema;
+pub mod digest;
+pub mod error;
+pub mod multi_domain_loader;
+#[cfg(feature = "remote")]
+pub(crate) mod remote;
 pub mod schema_loader;
+pub mod schema_source;
 pub mod validator;
@@ -0,0 +1,88 @@
+use crate::core::error::SchemaLoadError;
+use crate::core::schema_loader::SchemaLoader;
+use crate::core::schema_source::SchemaSource;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Loads schemas for any domain and version from one shared cache, populated by
+/// a single scan of the configured remote sources.
+#[derive(Clone)]
+pub struct MultiDomainLoader {
+    loader: SchemaLoader,
+}
+
+impl MultiDomainLoader {
+    /// Creates a loader over every domain in the configured remote sources.
+    pub fn new(schema_root: String) -> Self {
+        if schema_root.is_empty() {
+            panic!("Schema root must be specified.");
+        }
+
+        Self {
+            loader: SchemaLoader::with_remote_schemas(schema_root, String::new(), String::new()),
+        }
+    }
+
+    /// Creates a loader sharing the cache of an existing [`SchemaLoader`], whose
+    /// configured domain and version are ignored.
+    pub fn from_loader(loader: SchemaLoader) -> Self {
+        Self { loader }
+    }
+
+    /// Loads a schema by domain, version, category, and name.
+    pub fn load_schema(
+        &mut self,
+        domain: &str,
+        version: &str,
+        category: &str,
+        name: &str,
+    ) -> Result<Arc<Value>, SchemaLoadError> {
+        self.loader
+            .load_schema_internal(domain, version, category, name)
+    }
+
+    /// Returns every domain with cached schemas, sorted.
+    pub fn list_domains(&self) -> Vec<String> {
+        let mut domains: Vec<String> = self
+            .loader
+            .cache_keys()
+            .filter_map(|key| key.split('/').next())
+            .map(|domain| domain.to_string())
+            .collect();
+
+        domains.sort();
+        domains.dedup();
+        domains
+    }
+
+    /// Returns the `(category, name)` of every cached schema for a domain and
+    /// version, sorted.
+    pub fn list_schemas(&self, domain: &str, version: &str) -> Vec<(String, String)> {
+        let prefix = format!("{}/{}/", domain, version);
+        let mut schemas: Vec<(String, String)> = self
+            .loader
+            .cache_keys()
+            .filter_map(|key| key.strip_prefix(&prefix))
+            .filter_map(|rest| rest.split_once('/'))
+            .map(|(category, name)| (category.to_string(), name.to_string()))
+            .collect();
+
+        schemas.sort();
+        schemas
+    }
+
+    /// Sets a source consulted for schemas not found in the cache.
+    pub fn set_schema_source(&mut self, schema_source: Box<dyn SchemaSource>) {
+        self.loader.set_schema_source(schema_source);
+    }
+
+    /// Clears the shared schema cache.
+    pub fn clear_cache(&mut self) {
+        self.loader.clear_cache();
+    }
+
+    /// Gets the schema root.
+    pub fn get_schema_root(&self) -> &str {
+        self.loader.get_schema_root()
+    }
+}
@@ -0,0 +1,46 @@
+use serde_json::Value;
+
+const FETCH_TIMEOUT_SECS: u64 = 30;
+
+/// Fetches a JSON schema document over HTTP(S), rejecting URLs whose host is not
+/// in `allowed_hosts` so producers cannot point the service at internal endpoints.
+/// Hosts are compared case-insensitively. Redirects are not followed, since their
+/// targets would escape the allow-list.
+pub(crate) fn fetch_schema_document(url: &str, allowed_hosts: &[String]) -> Result<Value, String> {
+    let uri: ureq::http::Uri = url
+        .parse()
+        .map_err(|e| format!("Invalid schema URL {}: {}", url, e))?;
+
+    if !matches!(uri.scheme_str(), Some("http") | Some("https")) {
+        return Err(format!("Schema URL must use http or https: {}", url));
+    }
+
+    let host = uri.host().unwrap_or_default();
+    if !allowed_hosts
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    {
+        return Err(format!("Schema URL host is not allowed: {}", host));
+    }
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(FETCH_TIMEOUT_SECS)))
+        .max_redirects(0)
+        .build()
+        .into();
+
+    let mut response = agent
+        .get(url)
+        .call()
+        .map_err(|e| format!("Failed to fetch schema from {}: {}", url, e))?;
+    if response.status().is_redirection() {
+        return Err(format!("Schema URL redirects are not followed: {}", url));
+    }
+
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| format!("Failed to fetch schema from {}: {}", url, e))?;
+
+    serde_json::from_str(&body).map_err(|e| format!("Invalid schema JSON from {}: {}", url, e))
+}
@@ -1,9 +1,14 @@
-use anyhow::Result;
+use crate::core::error::SchemaLoadError;
+use crate::core::schema_source::SchemaSource;
+use crate::core::validator::schema_errors;
 use log::{error, info, warn};
 use serde::Deserialize;
 use serde_json::Value;
-use std::collections::HashMap;
-use std::io::Read;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::num::ParseIntError;
+use std::path::Path;
+use std::sync::Arc;
 
 use zip::read::ZipArchive;
 
@@ -14,16 +19,33 @@ struct SourcesConfig {
 
 const CONNECTION_TIMEOUT_SECS: u64 = 30;
 const MAX_RESPONSE_SIZE: u64 = 50 * 1024 * 1024;
+const DEFAULT_MAX_SCHEMA_SIZE: usize = 8 * 1024 * 1024;
+const DEFAULT_MAX_SCHEMA_DEPTH: usize = 64;
 
 /// Loads schemas from remote ZIP files.
 #[derive(Clone)]
 pub struct SchemaLoader {
-    schema_cache: HashMap<String, Value>,
+    schema_cache: HashMap<String, Arc<Value>>,
+    extended_schema_cache: HashMap<String, Arc<Value>>,
+    subschema_cache: HashMap<String, Value>,
+    raw_schema_cache: HashMap<String, Vec<u8>>,
+    remote_archive: Option<(String, Arc<[u8]>)>,
+    load_errors: Vec<String>,
+    schema_source: Option<Arc<dyn SchemaSource>>,
+    default_schema: Option<Arc<Value>>,
+    validate_on_load: bool,
+    keep_raw_schemas: bool,
+    max_schema_size: usize,
+    max_schema_depth: usize,
     schema_root: String,
     domain: String,
     version: String,
 }
 
+const DEFAULT_SCHEMA_ROOT: &str = "schemas";
+const DEFAULT_DOMAIN: &str = "bees";
+const DEFAULT_VERSION: &str = "v1";
+
 impl SchemaLoader {
     /// Creates a new SchemaLoader with the specified schema root, domain, and version.
     pub fn new(schema_root: String, domain: String, version: String) -> Self {
@@ -31,13 +53,36 @@ impl SchemaLoader {
             panic!("Schema root, domain, and version must be specified.");
         }
 
-        let mut loader = Self {
-            schema_cache: HashMap::new(),
-            schema_root,
-            domain,
-            version,
+        Self::with_remote_schemas(schema_root, domain, version)
+    }
+
+    /// Creates a SchemaLoader configured from the `PACTS_SCHEMA_ROOT`, `PACTS_DOMAIN`,
+    /// and `PACTS_VERSION` environment variables. Unset or empty variables fall back
+    /// to `schemas`, `bees`, and `v1` respectively.
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| default.to_string())
         };
 
+        Self::new(
+            var("PACTS_SCHEMA_ROOT", DEFAULT_SCHEMA_ROOT),
+            var("PACTS_DOMAIN", DEFAULT_DOMAIN),
+            var("PACTS_VERSION", DEFAULT_VERSION),
+        )
+    }
+
+    /// Creates a loader and populates its cache from the configured remote sources,
+    /// without checking that the domain and version are set.
+    pub(crate) fn with_remote_schemas(
+        schema_root: String,
+        domain: String,
+        version: String,
+    ) -> Self {
+        let mut loader = Self::empty(schema_root, domain, version);
+
         info!(
             "Initializing SchemaLoader with root: {}, domain: {}, version: {}",
             loader.schema_root, loader.domain, loader.version
@@ -55,23 +100,433 @@ impl SchemaLoader {
         loader
     }
 
-    /// Loads a schema from cache by category and name.
-    pub fn load_schema(&mut self, category: &str, name: &str) -> Value {
+    /// Creates a loader whose cache holds every `.json` file in a local archive,
+    /// keyed by its path inside the archive. Gzip-compressed tarballs, plain
+    /// tarballs, and ZIP files are accepted; nested directories are walked. The
+    /// domain and version are taken from the archive when all schemas share
+    /// them, and default to `bees` and `v1` otherwise.
+    #[cfg(feature = "archive")]
+    pub fn from_archive(path: &Path) -> Result<Self, SchemaLoadError> {
+        let bytes = std::fs::read(path)?;
+        let display_path = path.display().to_string();
+        let mut loader = Self::empty(
+            DEFAULT_SCHEMA_ROOT.to_string(),
+            DEFAULT_DOMAIN.to_string(),
+            DEFAULT_VERSION.to_string(),
+        );
+
+        let loaded = if bytes.starts_with(b"PK") {
+            loader.load_schemas_from_zip(bytes, &display_path)?
+        } else {
+            loader.load_schemas_from_tar(bytes, &display_path)?
+        };
+        if loaded == 0 {
+            return Err(SchemaLoadError::Source(format!(
+                "No schemas found in archive {}",
+                display_path
+            )));
+        }
+
+        let mut scopes = loader.schema_cache.keys().filter_map(|key| {
+            let mut parts = key.splitn(3, '/');
+            Some((parts.next()?.to_string(), parts.next()?.to_string()))
+        });
+        if let Some(first) = scopes.next() {
+            if scopes.all(|scope| scope == first) {
+                (loader.domain, loader.version) = first;
+            }
+        }
+
+        info!(
+            "Loaded {} schemas from archive {} for domain: {}, version: {}",
+            loaded, display_path, loader.domain, loader.version
+        );
+        Ok(loader)
+    }
+
+    fn empty(schema_root: String, domain: String, version: String) -> Self {
+        Self {
+            schema_cache: HashMap::new(),
+            extended_schema_cache: HashMap::new(),
+            subschema_cache: HashMap::new(),
+            raw_schema_cache: HashMap::new(),
+            remote_archive: None,
+            load_errors: Vec::new(),
+            schema_source: None,
+            default_schema: None,
+            validate_on_load: false,
+            keep_raw_schemas: false,
+            max_schema_size: DEFAULT_MAX_SCHEMA_SIZE,
+            max_schema_depth: DEFAULT_MAX_SCHEMA_DEPTH,
+            schema_root,
+            domain,
+            version,
+        }
+    }
+
+    /// Loads a schema from cache by category and name. The schema is shared with
+    /// the cache, so this does not copy it.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self), fields(domain = %self.domain, version = %self.version))
+    )]
+    pub fn load_schema(&mut self, category: &str, name: &str) -> Arc<Value> {
+        let (domain, version) = (self.domain.clone(), self.version.clone());
+
+        match self.load_schema_internal(&domain, &version, category, name) {
+            Ok(schema) => schema,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Borrows a cached schema by category and name without cloning it. Unlike
+    /// [`SchemaLoader::load_schema`], this does not consult the custom schema source
+    /// or resolve `extends`.
+    pub fn load_schema_ref(&self, category: &str, name: &str) -> Option<&Value> {
         let cache_key = format!("{}/{}/{}/{}", self.domain, self.version, category, name);
+        self.schema_cache.get(&cache_key).map(Arc::as_ref)
+    }
 
-        if let Some(schema) = self.schema_cache.get(&cache_key) {
-            return schema.clone();
+    /// Loads a schema from cache by version, category, and name.
+    pub fn load_schema_version(
+        &mut self,
+        version: &str,
+        category: &str,
+        name: &str,
+    ) -> Result<Arc<Value>, SchemaLoadError> {
+        let domain = self.domain.clone();
+        self.load_schema_internal(&domain, version, category, name)
+    }
+
+    /// Loads a schema by its fully-qualified id, e.g. `bees/v1/player/player_request`,
+    /// regardless of the loader's configured domain and version.
+    pub fn load_schema_by_id(&mut self, id: &str) -> Result<Arc<Value>, SchemaLoadError> {
+        match id.split('/').collect::<Vec<_>>().as_slice() {
+            [domain, version, category, name]
+                if [domain, version, category, name]
+                    .iter()
+                    .all(|s| !s.is_empty()) =>
+            {
+                self.load_schema_internal(domain, version, category, name)
+            }
+            _ => Err(SchemaLoadError::InvalidId(id.to_string())),
         }
+    }
+
+    /// Returns the original bytes of a cached schema as read from the schema archive,
+    /// without parsing or re-serializing them. Bytes are only kept for schemas read
+    /// while [`SchemaLoader::set_keep_raw_schemas`] is enabled; other schemas, and
+    /// those from a custom source or registered inline, are reported as not found.
+    pub fn load_schema_raw(&self, category: &str, name: &str) -> Result<Vec<u8>, SchemaLoadError> {
+        let cache_key = format!("{}/{}/{}/{}", self.domain, self.version, category, name);
+        self.raw_schema_cache
+            .get(&cache_key)
+            .cloned()
+            .ok_or(SchemaLoadError::NotFound(cache_key))
+    }
+
+    /// Loads a schema from the configured domain and version and returns the version it
+    /// declares itself, taken from its `$id` (e.g. `bees/v2/player/player_request`) or,
+    /// failing that, its numeric `version` field. Returns `None` if the schema cannot be
+    /// loaded or declares no version.
+    pub fn schema_version_of(&mut self, category: &str, name: &str) -> Option<u32> {
+        let (domain, version) = (self.domain.clone(), self.version.clone());
+        let schema = self
+            .load_schema_internal(&domain, &version, category, name)
+            .ok()?;
+
+        let from_id = schema
+            .get("$id")
+            .and_then(Value::as_str)
+            .and_then(|id| id.split('/').nth(1))
+            .and_then(parse_version_number);
+
+        from_id.or_else(|| {
+            schema
+                .get("version")
+                .and_then(Value::as_u64)
+                .and_then(|version| u32::try_from(version).ok())
+        })
+    }
 
-        panic!(
-            "Schema not found in cache: {}/{}/{}/{}",
-            self.domain, self.version, category, name
+    /// Loads only the sub-schema at the given JSON Pointer (e.g. `/properties/address`)
+    /// within a schema. Sub-schemas are cached by schema key and pointer.
+    pub fn load_schema_subschema(
+        &mut self,
+        category: &str,
+        name: &str,
+        pointer: &str,
+    ) -> Result<Value, SchemaLoadError> {
+        let cache_key = format!(
+            "{}/{}/{}/{}#{}",
+            self.domain, self.version, category, name, pointer
         );
+
+        if let Some(subschema) = self.subschema_cache.get(&cache_key) {
+            return Ok(subschema.clone());
+        }
+
+        let (domain, version) = (self.domain.clone(), self.version.clone());
+        let schema = self.load_schema_internal(&domain, &version, category, name)?;
+        let subschema = schema
+            .pointer(pointer)
+            .cloned()
+            .ok_or_else(|| SchemaLoadError::NotFound(cache_key.clone()))?;
+
+        self.subschema_cache.insert(cache_key, subschema.clone());
+        Ok(subschema)
+    }
+
+    /// Returns the `(category, name)` of every cached schema for the configured
+    /// domain and version, sorted.
+    pub fn list_schemas(&self) -> Vec<(String, String)> {
+        let prefix = format!("{}/{}/", self.domain, self.version);
+        let mut schemas: Vec<(String, String)> = self
+            .schema_cache
+            .keys()
+            .filter_map(|key| key.strip_prefix(&prefix))
+            .filter_map(|rest| rest.split_once('/'))
+            .map(|(category, name)| (category.to_string(), name.to_string()))
+            .collect();
+
+        schemas.sort();
+        schemas
+    }
+
+    /// Returns the `(version, category, name)` of every cached schema for the
+    /// configured domain across all versions, sorted.
+    pub fn list_all_schemas(&self) -> Vec<(String, String, String)> {
+        let prefix = format!("{}/", self.domain);
+        let mut schemas: Vec<(String, String, String)> = self
+            .schema_cache
+            .keys()
+            .filter_map(|key| key.strip_prefix(&prefix))
+            .filter_map(|rest| {
+                let mut parts = rest.splitn(3, '/');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(version), Some(category), Some(name)) => {
+                        Some((version.to_string(), category.to_string(), name.to_string()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+
+        schemas.sort();
+        schemas
+    }
+
+    /// Returns every category with cached schemas for the configured domain and
+    /// version, sorted.
+    pub fn list_categories(&self) -> Vec<String> {
+        let mut categories: Vec<String> = self
+            .list_schemas()
+            .into_iter()
+            .map(|(category, _)| category)
+            .collect();
+
+        categories.dedup();
+        categories
+    }
+
+    /// Returns the names of the cached schemas in a category for the configured
+    /// domain and version, sorted. An unknown category has no schemas.
+    pub fn list_category(&self, category: &str) -> Vec<String> {
+        self.list_schemas()
+            .into_iter()
+            .filter(|(schema_category, _)| schema_category == category)
+            .map(|(_, name)| name)
+            .collect()
+    }
+
+    /// Returns the highest available version of the domain that is not newer
+    /// than the requested one, e.g. `v2` when `v3` is requested but only `v1`
+    /// and `v2` are available.
+    pub fn resolve_version(&self, requested: &str) -> Option<String> {
+        let requested = parse_version_number(requested)?;
+
+        self.schema_cache
+            .keys()
+            .filter_map(|key| {
+                let mut parts = key.split('/');
+                match (parts.next(), parts.next()) {
+                    (Some(domain), Some(version)) if domain == self.domain => {
+                        parse_version_number(version).map(|number| (number, version))
+                    }
+                    _ => None,
+                }
+            })
+            .filter(|(number, _)| *number <= requested)
+            .max_by_key(|(number, _)| *number)
+            .map(|(_, version)| version.to_string())
+    }
+
+    /// Reads every schema into the cache and returns the number available across
+    /// all categories of the configured domain and version. The remote archive
+    /// downloaded when the loader was created is reused, so the configured sources
+    /// are only fetched again if no archive was downloaded. Entries that cannot be
+    /// read or parsed are skipped and recorded in [`SchemaLoader::get_load_errors`].
+    pub fn preload_all(&mut self) -> Result<usize, SchemaLoadError> {
+        self.load_errors.clear();
+        match self.remote_archive.clone() {
+            Some((url, bytes)) => {
+                self.load_schemas_from_zip(bytes, &url)?;
+            }
+            None => {
+                self.load_remote_schemas()?;
+            }
+        }
+
+        let loaded = self.list_schemas().len();
+        info!(
+            "Preloaded {} schemas for {}/{} in categories: {}",
+            loaded,
+            self.domain,
+            self.version,
+            self.list_categories().join(", ")
+        );
+
+        for load_error in &self.load_errors {
+            warn!("Skipped during preload: {}", load_error);
+        }
+
+        Ok(loaded)
+    }
+
+    /// Sets a source consulted for schemas that are not in the cache. Schemas it
+    /// returns are cached.
+    pub fn set_schema_source(&mut self, schema_source: Box<dyn SchemaSource>) {
+        self.schema_source = Some(Arc::from(schema_source));
+    }
+
+    /// Sets whether schemas loaded from now on, e.g. by [`SchemaLoader::preload_all`]
+    /// or from the schema source, are checked with [`crate::Validator::validate_schema`].
+    /// Malformed schemas are then rejected instead of cached.
+    pub fn set_validate_on_load(&mut self, validate_on_load: bool) {
+        self.validate_on_load = validate_on_load;
+    }
+
+    /// Returns true if schemas are checked for structural problems when loaded.
+    pub fn is_validate_on_load(&self) -> bool {
+        self.validate_on_load
+    }
+
+    /// Sets whether the original bytes of schemas read from an archive from now on,
+    /// e.g. by [`SchemaLoader::preload_all`], are kept for
+    /// [`SchemaLoader::load_schema_raw`]. Off by default, since it keeps a second
+    /// copy of every schema; disabling it drops the bytes kept so far.
+    pub fn set_keep_raw_schemas(&mut self, keep_raw_schemas: bool) {
+        self.keep_raw_schemas = keep_raw_schemas;
+        if !keep_raw_schemas {
+            self.raw_schema_cache.clear();
+        }
+    }
+
+    /// Returns true if the original bytes of archive schemas are kept.
+    pub fn is_keep_raw_schemas(&self) -> bool {
+        self.keep_raw_schemas
+    }
+
+    /// Sets the largest schema, in bytes, read from an archive. Larger entries are
+    /// skipped without being parsed. Defaults to 8MiB.
+    pub fn set_max_schema_size(&mut self, max_schema_size: usize) {
+        self.max_schema_size = max_schema_size;
+    }
+
+    /// Returns the largest schema, in bytes, read from an archive.
+    pub fn get_max_schema_size(&self) -> usize {
+        self.max_schema_size
+    }
+
+    /// Sets the deepest nesting of objects and arrays allowed in a schema read from
+    /// an archive. Deeper entries are skipped without being parsed. Defaults to 64.
+    pub fn set_max_schema_depth(&mut self, max_schema_depth: usize) {
+        self.max_schema_depth = max_schema_depth;
+    }
+
+    /// Returns the deepest nesting allowed in a schema read from an archive.
+    pub fn get_max_schema_depth(&self) -> usize {
+        self.max_schema_depth
+    }
+
+    /// Sets a schema returned in place of any schema that is neither cached nor
+    /// provided by the schema source, so unknown schemas are checked against it
+    /// instead of failing to load. The default is not cached under the missing
+    /// schema's key, so a schema registered later still takes precedence.
+    pub fn set_default_schema(&mut self, schema: Value) {
+        self.default_schema = Some(Arc::new(schema));
+    }
+
+    /// Returns the schema used for schemas that cannot be found, if one is set.
+    pub fn get_default_schema(&self) -> Option<&Value> {
+        self.default_schema.as_deref()
+    }
+
+    /// Returns the errors for entries skipped during the last load.
+    pub fn get_load_errors(&self) -> &[String] {
+        &self.load_errors
+    }
+
+    /// Registers an inline schema under the configured domain and version, so later
+    /// loads of `category`/`name` return it without touching the filesystem. Replaces
+    /// any schema already cached under that key.
+    pub fn register_schema(&mut self, category: &str, name: &str, schema: Value) {
+        let cache_key = format!("{}/{}/{}/{}", self.domain, self.version, category, name);
+        let subschema_prefix = format!("{}#", cache_key);
+        self.subschema_cache
+            .retain(|key, _| !key.starts_with(&subschema_prefix));
+        self.raw_schema_cache.remove(&cache_key);
+        self.schema_cache.insert(cache_key, Arc::new(schema));
+        self.extended_schema_cache.clear();
+    }
+
+    /// Writes every parsed schema in the cache to a single JSON file at `path`,
+    /// keyed by `domain/version/category/name`, for [`SchemaLoader::load_cache`].
+    pub fn dump_cache(&self, path: &Path) -> Result<(), SchemaLoadError> {
+        let cache: BTreeMap<&str, &Value> = self
+            .schema_cache
+            .iter()
+            .map(|(key, schema)| (key.as_str(), schema.as_ref()))
+            .collect();
+
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer(&mut writer, &cache)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Adds the schemas in a file written by [`SchemaLoader::dump_cache`] to the
+    /// cache, replacing any cached under the same keys, and returns how many were
+    /// read. Schemas missing from the file are still loaded from the schema
+    /// source on demand.
+    pub fn load_cache(&mut self, path: &Path) -> Result<usize, SchemaLoadError> {
+        let reader = BufReader::new(std::fs::File::open(path)?);
+        let cache: HashMap<String, Value> = serde_json::from_reader(reader)?;
+        let loaded = cache.len();
+
+        for (cache_key, schema) in cache {
+            let subschema_prefix = format!("{}#", cache_key);
+            self.subschema_cache
+                .retain(|key, _| !key.starts_with(&subschema_prefix));
+            self.raw_schema_cache.remove(&cache_key);
+            self.schema_cache.insert(cache_key, Arc::new(schema));
+        }
+        self.extended_schema_cache.clear();
+
+        info!(
+            "Loaded {} schemas from cache file {}",
+            loaded,
+            path.display()
+        );
+        Ok(loaded)
     }
 
     /// Clears all cached schemas.
     pub fn clear_cache(&mut self) {
         self.schema_cache.clear();
+        self.extended_schema_cache.clear();
+        self.subschema_cache.clear();
+        self.raw_schema_cache.clear();
     }
 
     /// Returns the schema root directory.
@@ -90,20 +545,136 @@ impl SchemaLoader {
     }
 
     /// Returns the parsed version as an integer.
+    ///
+    /// This is lossy: every `v` is removed before parsing and any version that
+    /// fails to parse (e.g. `v1.2` or `latest`) falls back to `1`. Prefer
+    /// [`SchemaLoader::try_parsed_version`] when malformed versions must be detected.
     pub fn get_parsed_version(&self) -> i32 {
         self.version.replace("v", "").parse().unwrap_or(1)
     }
 
-    fn load_remote_schemas(&mut self) -> Result<()> {
+    /// Returns the parsed version as an integer, stripping only a leading `v`.
+    /// Returns an error if the remainder is not a non-negative integer.
+    pub fn try_parsed_version(&self) -> Result<u32, ParseIntError> {
+        self.version
+            .strip_prefix('v')
+            .unwrap_or(&self.version)
+            .parse()
+    }
+
+    /// Returns the `domain/version/category/name` keys of every cached schema.
+    pub(crate) fn cache_keys(&self) -> impl Iterator<Item = &String> {
+        self.schema_cache.keys()
+    }
+
+    /// Loads a schema and, if it names a base schema with `"extends": "category/name"`,
+    /// merges the base's `required` and `properties` into it. The extending schema's
+    /// properties take precedence on conflict. Merged schemas are cached until the
+    /// schema cache changes. A schema that cannot be found falls
+    /// back to the default schema, if one is set.
+    pub(crate) fn load_schema_internal(
+        &mut self,
+        domain: &str,
+        version: &str,
+        category: &str,
+        name: &str,
+    ) -> Result<Arc<Value>, SchemaLoadError> {
+        let cache_key = format!("{}/{}/{}/{}", domain, version, category, name);
+        match self.load_extended_schema(domain, version, category, name, &mut Vec::new()) {
+            Err(SchemaLoadError::NotFound(key)) if key == cache_key => match &self.default_schema {
+                Some(default_schema) => Ok(Arc::clone(default_schema)),
+                None => Err(SchemaLoadError::NotFound(key)),
+            },
+            result => result,
+        }
+    }
+
+    fn load_extended_schema(
+        &mut self,
+        domain: &str,
+        version: &str,
+        category: &str,
+        name: &str,
+        chain: &mut Vec<String>,
+    ) -> Result<Arc<Value>, SchemaLoadError> {
+        let cache_key = format!("{}/{}/{}/{}", domain, version, category, name);
+        if let Some(merged) = self.extended_schema_cache.get(&cache_key) {
+            return Ok(Arc::clone(merged));
+        }
+
+        let schema = self.fetch_schema(domain, version, category, name)?;
+        let extends = match schema.get("extends").and_then(|e| e.as_str()) {
+            Some(extends) => extends.to_string(),
+            None => return Ok(schema),
+        };
+
+        if chain.contains(&cache_key) {
+            return Err(SchemaLoadError::InvalidSchema(format!(
+                "{}: circular extends via {}",
+                cache_key, extends
+            )));
+        }
+        let (base_category, base_name) = extends.split_once('/').ok_or_else(|| {
+            SchemaLoadError::InvalidSchema(format!(
+                "{}: 'extends' must be category/name, got '{}'",
+                cache_key, extends
+            ))
+        })?;
+
+        chain.push(cache_key.clone());
+        let base = self.load_extended_schema(domain, version, base_category, base_name, chain)?;
+        chain.pop();
+
+        let merged = Arc::new(merge_extends(&base, &schema));
+        self.extended_schema_cache
+            .insert(cache_key, Arc::clone(&merged));
+        Ok(merged)
+    }
+
+    fn fetch_schema(
+        &mut self,
+        domain: &str,
+        version: &str,
+        category: &str,
+        name: &str,
+    ) -> Result<Arc<Value>, SchemaLoadError> {
+        let cache_key = format!("{}/{}/{}/{}", domain, version, category, name);
+
+        if let Some(schema) = self.schema_cache.get(&cache_key) {
+            return Ok(Arc::clone(schema));
+        }
+
+        if let Some(schema_source) = &self.schema_source {
+            if let Some(schema) = schema_source.fetch(domain, version, category, name)? {
+                if self.validate_on_load {
+                    let errors = schema_errors(&schema);
+                    if !errors.is_empty() {
+                        return Err(SchemaLoadError::InvalidSchema(format!(
+                            "{}: {}",
+                            cache_key,
+                            errors.join("; ")
+                        )));
+                    }
+                }
+                info!("Loaded schema from custom source: {}", cache_key);
+                let schema = Arc::new(schema);
+                self.schema_cache.insert(cache_key, Arc::clone(&schema));
+                return Ok(schema);
+            }
+        }
+
+        Err(SchemaLoadError::NotFound(cache_key))
+    }
+
+    fn load_remote_schemas(&mut self) -> Result<usize, SchemaLoadError> {
         let sources = self.load_sources_config()?;
 
         for source in sources {
-            let cache_size_before = self.schema_cache.len();
             match self.load_schemas_from_zip_url(&source) {
-                Ok(_) => {
-                    if self.schema_cache.len() > cache_size_before {
+                Ok(loaded) => {
+                    if loaded > 0 {
                         info!("Successfully loaded schemas from: {}", source);
-                        return Ok(());
+                        return Ok(loaded);
                     }
                     warn!(
                         "ZIP from {} contained no loadable schemas, trying next source",
@@ -116,25 +687,28 @@ impl SchemaLoader {
             }
         }
 
-        Err(anyhow::anyhow!(
-            "Sources could not be read or found to populate schemas."
+        Err(SchemaLoadError::Source(
+            "Sources could not be read or found to populate schemas.".to_string(),
         ))
     }
 
-    fn load_sources_config(&self) -> Result<Vec<String>> {
+    fn load_sources_config(&self) -> Result<Vec<String>, SchemaLoadError> {
         const SOURCES_YAML: &str = include_str!(concat!(env!("OUT_DIR"), "/sources.yaml"));
 
-        let config: SourcesConfig = serde_yaml::from_str(SOURCES_YAML)
-            .map_err(|e| anyhow::anyhow!("Failed to parse embedded sources.yaml: {}", e))?;
+        let config: SourcesConfig = serde_yaml::from_str(SOURCES_YAML).map_err(|e| {
+            SchemaLoadError::Source(format!("Failed to parse embedded sources.yaml: {}", e))
+        })?;
 
         if config.sources.is_empty() {
-            return Err(anyhow::anyhow!("No sources defined in sources.yaml"));
+            return Err(SchemaLoadError::Source(
+                "No sources defined in sources.yaml".to_string(),
+            ));
         }
 
         Ok(config.sources)
     }
 
-    fn load_schemas_from_zip_url(&mut self, url: &str) -> Result<()> {
+    fn load_schemas_from_zip_url(&mut self, url: &str) -> Result<usize, SchemaLoadError> {
         let agent: ureq::Agent = ureq::Agent::config_builder()
             .timeout_global(Some(std::time::Duration::from_secs(
                 CONNECTION_TIMEOUT_SECS,
@@ -142,10 +716,9 @@ impl SchemaLoader {
             .build()
             .into();
 
-        let mut response = agent
-            .get(url)
-            .call()
-            .map_err(|e| anyhow::anyhow!("HTTP request to {} failed: {}", url, e))?;
+        let mut response = agent.get(url).call().map_err(|e| {
+            SchemaLoadError::Source(format!("HTTP request to {} failed: {}", url, e))
+        })?;
 
         let mut bytes = Vec::new();
         response
@@ -154,56 +727,287 @@ impl SchemaLoader {
             .take(MAX_RESPONSE_SIZE)
             .read_to_end(&mut bytes)?;
 
+        self.load_remote_archive(bytes, url)
+    }
+
+    /// Caches the schemas in a downloaded ZIP archive and, if any were loaded,
+    /// keeps the archive so [`SchemaLoader::preload_all`] can reload it without
+    /// downloading it again.
+    pub(crate) fn load_remote_archive(
+        &mut self,
+        bytes: Vec<u8>,
+        url: &str,
+    ) -> Result<usize, SchemaLoadError> {
+        let bytes: Arc<[u8]> = bytes.into();
+        let loaded = self.load_schemas_from_zip(bytes.clone(), url)?;
+        if loaded > 0 {
+            self.remote_archive = Some((url.to_string(), bytes));
+        }
+        Ok(loaded)
+    }
+
+    /// Caches every JSON schema in a ZIP archive, keyed by the last three
+    /// directories of its path and its file name.
+    pub(crate) fn load_schemas_from_zip<B: AsRef<[u8]>>(
+        &mut self,
+        bytes: B,
+        url: &str,
+    ) -> Result<usize, SchemaLoadError> {
         let reader = std::io::Cursor::new(bytes);
-        let mut zip = ZipArchive::new(reader)?;
+        let mut zip = ZipArchive::new(reader).map_err(|e| invalid_archive(url, e))?;
+        let mut loaded = 0;
 
         for i in 0..zip.len() {
-            let mut entry = zip.by_index(i)?;
+            let mut entry = zip.by_index(i).map_err(|e| invalid_archive(url, e))?;
 
             if !entry.is_dir() && entry.name().ends_with(".json") {
                 let entry_name = entry.name().to_string();
 
                 let mut content = String::new();
-                if let Err(e) = entry.read_to_string(&mut content) {
+                let limit = self.max_schema_size as u64 + 1;
+                if let Err(e) = (&mut entry).take(limit).read_to_string(&mut content) {
                     error!("Failed to read entry {} (index {}): {}", entry_name, i, e);
+                    self.load_errors
+                        .push(format!("Failed to read entry {}: {}", entry_name, e));
                     continue;
                 }
 
-                let schema: Value = match serde_json::from_str(&content) {
-                    Ok(s) => s,
-                    Err(e) => {
-                        error!(
-                            "Failed to parse JSON for entry {} (index {}): {}",
-                            entry_name, i, e
-                        );
-                        continue;
-                    }
-                };
-
-                let entry_path = entry_name.as_str();
-                let last_slash = entry_path.rfind('/');
-                let (category_path, file_name) = match last_slash {
-                    Some(pos) => (&entry_path[..pos], &entry_path[pos + 1..]),
-                    None => ("", entry_path),
-                };
-
-                let path_parts: Vec<&str> = category_path.split('/').collect();
-                if path_parts.len() >= 3 {
-                    let entry_domain = path_parts[path_parts.len() - 3];
-                    let entry_version = path_parts[path_parts.len() - 2];
-                    let entry_category = path_parts[path_parts.len() - 1];
-                    let schema_name = file_name.trim_end_matches(".json");
-
-                    let cache_key = format!(
-                        "{}/{}/{}/{}",
-                        entry_domain, entry_version, entry_category, schema_name
-                    );
-                    self.schema_cache.insert(cache_key.clone(), schema);
-                    info!("Loaded schema into cache: {}", cache_key);
+                if self.cache_archive_entry(&entry_name, content) {
+                    loaded += 1;
                 }
             }
         }
 
+        Ok(loaded)
+    }
+
+    /// Caches every JSON schema in a tar archive, gzip-compressed or not, keyed
+    /// like [`SchemaLoader::load_schemas_from_zip`]. Directory entries are skipped.
+    #[cfg(feature = "archive")]
+    fn load_schemas_from_tar(
+        &mut self,
+        bytes: Vec<u8>,
+        path: &str,
+    ) -> Result<usize, SchemaLoadError> {
+        let reader: Box<dyn Read> = if bytes.starts_with(&[0x1f, 0x8b]) {
+            Box::new(flate2::read::GzDecoder::new(std::io::Cursor::new(bytes)))
+        } else {
+            Box::new(std::io::Cursor::new(bytes))
+        };
+        let mut archive = tar::Archive::new(reader);
+        let mut loaded = 0;
+
+        let entries = archive.entries().map_err(|e| {
+            SchemaLoadError::Source(format!("Invalid schema archive {}: {}", path, e))
+        })?;
+        for entry in entries {
+            let mut entry = entry.map_err(|e| {
+                SchemaLoadError::Source(format!("Invalid schema archive {}: {}", path, e))
+            })?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let entry_name = match entry.path() {
+                Ok(entry_path) => entry_path.to_string_lossy().replace('\\', "/"),
+                Err(e) => {
+                    self.load_errors
+                        .push(format!("Invalid entry path in {}: {}", path, e));
+                    continue;
+                }
+            };
+            if !entry_name.ends_with(".json") {
+                continue;
+            }
+
+            let mut content = String::new();
+            let limit = self.max_schema_size as u64 + 1;
+            if let Err(e) = (&mut entry).take(limit).read_to_string(&mut content) {
+                error!("Failed to read entry {}: {}", entry_name, e);
+                self.load_errors
+                    .push(format!("Failed to read entry {}: {}", entry_name, e));
+                continue;
+            }
+
+            if self.cache_archive_entry(&entry_name, content) {
+                loaded += 1;
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /// Rejects schema text larger than the size limit or nested deeper than the
+    /// depth limit, before it is parsed.
+    fn check_schema_limits(&self, name: &str, content: &str) -> Result<(), SchemaLoadError> {
+        if content.len() > self.max_schema_size {
+            return Err(SchemaLoadError::LimitExceeded(format!(
+                "Schema {} exceeds max size {}",
+                name,
+                format_size(self.max_schema_size)
+            )));
+        }
+        if json_depth_exceeds(content, self.max_schema_depth) {
+            return Err(SchemaLoadError::LimitExceeded(format!(
+                "Schema {} exceeds max depth {}",
+                name, self.max_schema_depth
+            )));
+        }
         Ok(())
     }
+
+    /// Parses an archive entry and caches it under the domain, version, and
+    /// category taken from the last three directories of its path. Returns
+    /// false, recording why, if the entry was not cached.
+    fn cache_archive_entry(&mut self, entry_name: &str, content: String) -> bool {
+        if let Err(e) = self.check_schema_limits(entry_name, &content) {
+            error!("Skipped entry: {}", e);
+            self.load_errors.push(e.to_string());
+            return false;
+        }
+
+        let schema: Value = match serde_json::from_str(&content) {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Failed to parse JSON for entry {}: {}", entry_name, e);
+                self.load_errors.push(format!(
+                    "Failed to parse JSON for entry {}: {}",
+                    entry_name, e
+                ));
+                return false;
+            }
+        };
+
+        if self.validate_on_load {
+            let errors = schema_errors(&schema);
+            if !errors.is_empty() {
+                error!("Rejected malformed schema {}: {:?}", entry_name, errors);
+                self.load_errors.push(format!(
+                    "Malformed schema {}: {}",
+                    entry_name,
+                    errors.join("; ")
+                ));
+                return false;
+            }
+        }
+
+        let (category_path, file_name) = match entry_name.rfind('/') {
+            Some(pos) => (&entry_name[..pos], &entry_name[pos + 1..]),
+            None => ("", entry_name),
+        };
+
+        let path_parts: Vec<&str> = category_path.split('/').collect();
+        if path_parts.len() < 3 {
+            return false;
+        }
+
+        let entry_domain = path_parts[path_parts.len() - 3];
+        let entry_version = path_parts[path_parts.len() - 2];
+        let entry_category = path_parts[path_parts.len() - 1];
+        let schema_name = file_name.trim_end_matches(".json");
+
+        let cache_key = format!(
+            "{}/{}/{}/{}",
+            entry_domain, entry_version, entry_category, schema_name
+        );
+        self.schema_cache
+            .insert(cache_key.clone(), Arc::new(schema));
+        self.extended_schema_cache.clear();
+        if self.keep_raw_schemas {
+            self.raw_schema_cache
+                .insert(cache_key.clone(), content.into_bytes());
+        } else {
+            self.raw_schema_cache.remove(&cache_key);
+        }
+        info!("Loaded schema into cache: {}", cache_key);
+        true
+    }
+}
+
+/// Returns true if JSON text nests objects and arrays deeper than `max_depth`,
+/// without parsing it. Brackets inside strings are ignored.
+fn json_depth_exceeds(text: &str, max_depth: usize) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for byte in text.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > max_depth {
+                    return true;
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Formats a byte count compactly, e.g. `1MiB`, `512KiB` or `100 bytes`.
+fn format_size(bytes: usize) -> String {
+    const KIB: usize = 1024;
+    const MIB: usize = 1024 * KIB;
+    match bytes {
+        0 => "0 bytes".to_string(),
+        _ if bytes.is_multiple_of(MIB) => format!("{}MiB", bytes / MIB),
+        _ if bytes.is_multiple_of(KIB) => format!("{}KiB", bytes / KIB),
+        _ => format!("{} bytes", bytes),
+    }
+}
+
+/// Merges a base schema's `required` and `properties` into an extending schema,
+/// dropping its `extends`. Existing properties of the extending schema win.
+fn merge_extends(base: &Value, schema: &Value) -> Value {
+    let mut merged = schema.clone();
+    let merged_obj = match merged.as_object_mut() {
+        Some(merged_obj) => merged_obj,
+        None => return merged,
+    };
+    merged_obj.shift_remove("extends");
+
+    if let Some(base_required) = base.get("required").and_then(|r| r.as_array()) {
+        let mut required = base_required.clone();
+        if let Some(own_required) = schema.get("required").and_then(|r| r.as_array()) {
+            for field in own_required {
+                if !required.contains(field) {
+                    required.push(field.clone());
+                }
+            }
+        }
+        merged_obj.insert("required".to_string(), Value::Array(required));
+    }
+
+    if let Some(base_properties) = base.get("properties").and_then(|p| p.as_object()) {
+        let mut properties = base_properties.clone();
+        if let Some(own_properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (property_name, property_schema) in own_properties {
+                properties.insert(property_name.clone(), property_schema.clone());
+            }
+        }
+        merged_obj.insert("properties".to_string(), Value::Object(properties));
+    }
+
+    merged
+}
+
+fn invalid_archive(url: &str, e: zip::result::ZipError) -> SchemaLoadError {
+    SchemaLoadError::Source(format!("Invalid schema archive from {}: {}", url, e))
+}
+
+pub(crate) fn parse_version_number(version: &str) -> Option<u32> {
+    version.strip_prefix('v').unwrap_or(version).parse().ok()
 }
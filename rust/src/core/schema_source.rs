@@ -0,0 +1,15 @@
+use crate::core::error::SchemaLoadError;
+use serde_json::Value;
+
+/// A source of schemas consulted by [`crate::SchemaLoader`] when a schema is not
+/// in its cache, e.g. a database, object store, or key-value store.
+pub trait SchemaSource: Send + Sync {
+    /// Fetches a schema, returning `Ok(None)` if this source does not have it.
+    fn fetch(
+        &self,
+        domain: &str,
+        version: &str,
+        category: &str,
+        name: &str,
+    ) -> Result<Option<Value>, SchemaLoadError>;
+}
@@ -1,33 +1,108 @@
-use crate::{Envelope, SchemaLoader};
+use crate::core::array_stream::ArrayElements;
+use crate::core::compatibility::{self, Incompatibility};
+use crate::core::compiled_schema::{CompiledItems, CompiledSchema};
+use crate::{Envelope, Header, SchemaLoader};
+use chrono::{Duration, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::sync::{Arc, Mutex, PoisonError};
 
 /// Result of a validation operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
     pub valid: bool,
     pub errors: Vec<String>,
+    pub schema_version: Option<String>,
+    pub schema_ref: Option<String>,
+    pub schema_source: Option<String>,
+    pub schema_digest: Option<String>,
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// The [`ValidationError`] code of each error, by position. Errors added
+    /// without a code, e.g. through [`ValidationResult::failure`], have none.
+    /// Codes are serialized but not deserialized.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Vec::is_empty")]
+    pub error_codes: Vec<&'static str>,
 }
 
 impl ValidationResult {
     /// Creates a new validation result with the given status and errors.
     pub fn new(valid: bool, errors: Vec<String>) -> Self {
-        Self { valid, errors }
+        Self {
+            valid,
+            errors,
+            schema_version: None,
+            schema_ref: None,
+            schema_source: None,
+            schema_digest: None,
+            truncated: false,
+            warnings: Vec::new(),
+            error_codes: Vec::new(),
+        }
     }
 
-    /// Creates a successful validation result with no errors.
-    pub fn success() -> Self {
+    /// Creates a result from coded errors, valid if there are none.
+    pub fn from_errors(errors: Vec<ValidationError>) -> Self {
+        let (error_codes, errors): (Vec<_>, Vec<_>) = errors
+            .into_iter()
+            .map(|error| (error.code, error.message))
+            .unzip();
         Self {
-            valid: true,
-            errors: Vec::new(),
+            error_codes,
+            ..Self::new(errors.is_empty(), errors)
         }
     }
 
+    /// Creates a successful validation result with no errors.
+    pub fn success() -> Self {
+        Self::new(true, Vec::new())
+    }
+
     /// Creates a failed validation result with the given errors.
     pub fn failure(errors: Vec<String>) -> Self {
-        Self {
-            valid: false,
-            errors,
-        }
+        Self::new(false, errors)
+    }
+
+    /// Attaches the reference of the schema validated against, e.g. `bees/v1/player/player_request`.
+    pub fn with_schema_ref(mut self, schema_ref: String) -> Self {
+        self.schema_ref = Some(schema_ref);
+        self
+    }
+
+    /// Attaches the source the schema was loaded from.
+    pub fn with_schema_source(mut self, schema_source: String) -> Self {
+        self.schema_source = Some(schema_source);
+        self
+    }
+
+    /// Attaches a digest of the schema validated against.
+    pub fn with_schema_digest(mut self, schema_digest: String) -> Self {
+        self.schema_digest = Some(schema_digest);
+        self
+    }
+
+    /// Appends an error with its code and marks the result invalid, keeping
+    /// the other fields. Earlier errors without a code are recorded as
+    /// [`ValidationError::UNCLASSIFIED`] so codes stay aligned with errors.
+    pub fn with_error(mut self, error: ValidationError) -> Self {
+        self.error_codes
+            .resize(self.errors.len(), ValidationError::UNCLASSIFIED);
+        self.errors.push(error.message);
+        self.error_codes.push(error.code);
+        self.valid = false;
+        self
+    }
+
+    /// Attaches a warning that does not affect validity.
+    pub fn with_warning(mut self, warning: String) -> Self {
+        self.warnings.push(warning);
+        self
     }
 
     /// Returns true if validation passed.
@@ -40,6 +115,55 @@ impl ValidationResult {
         &self.errors
     }
 
+    /// Returns each error with its code. Errors recorded without a code are
+    /// reported as [`ValidationError::UNCLASSIFIED`].
+    pub fn get_error_details(&self) -> Vec<ValidationError> {
+        self.errors
+            .iter()
+            .enumerate()
+            .map(|(index, message)| ValidationError {
+                code: self
+                    .error_codes
+                    .get(index)
+                    .copied()
+                    .unwrap_or(ValidationError::UNCLASSIFIED),
+                message: message.clone(),
+                path: String::new(),
+            })
+            .collect()
+    }
+
+    /// Returns the warnings collected during validation.
+    pub fn get_warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Returns true if validation stopped early or errors were cut off, so more
+    /// errors may exist than are listed.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the schema version validated against, if it was negotiated.
+    pub fn get_schema_version(&self) -> Option<&str> {
+        self.schema_version.as_deref()
+    }
+
+    /// Returns the reference of the schema validated against, if known.
+    pub fn get_schema_ref(&self) -> Option<&str> {
+        self.schema_ref.as_deref()
+    }
+
+    /// Returns the source the schema was loaded from, if known.
+    pub fn get_schema_source(&self) -> Option<&str> {
+        self.schema_source.as_deref()
+    }
+
+    /// Returns the digest of the schema validated against, if known.
+    pub fn get_schema_digest(&self) -> Option<&str> {
+        self.schema_digest.as_deref()
+    }
+
     /// Returns true if there are any errors.
     pub fn has_errors(&self) -> bool {
         !self.errors.is_empty()
@@ -53,143 +177,2270 @@ impl ValidationResult {
             self.errors.join("; ")
         }
     }
+
+    /// Renders the result for terminal output: `✓ Valid` on success, otherwise
+    /// one `✗`-prefixed line per error followed by an error count.
+    pub fn to_pretty_string(&self) -> String {
+        if self.valid {
+            return "✓ Valid".to_string();
+        }
+
+        let mut lines: Vec<String> = self
+            .errors
+            .iter()
+            .map(|error| format!("✗ {}", error))
+            .collect();
+        lines.push(match self.errors.len() {
+            1 => "1 error".to_string(),
+            count => format!("{} errors", count),
+        });
+        lines.join("\n")
+    }
+}
+
+/// A validation error message with a stable, machine-readable code, so clients
+/// can localize the message or react to specific failures.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    pub code: &'static str,
+    pub message: String,
+    /// JSON pointer to the value the error is about, e.g. `/tags/1`; empty for
+    /// the data as a whole. Paths are not kept in [`ValidationResult`].
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub path: String,
+}
+
+impl ValidationError {
+    /// The header is missing entirely.
+    pub const HEADER_MISSING: &'static str = "HEADER_MISSING";
+    /// The header lacks a schema category, name, or version.
+    pub const HEADER_FIELD_MISSING: &'static str = "HEADER_FIELD_MISSING";
+    /// The envelope's `expires_at` has passed.
+    pub const ENVELOPE_EXPIRED: &'static str = "ENVELOPE_EXPIRED";
+    /// The header timestamp is further from now than the allowed clock skew.
+    pub const CLOCK_SKEW_EXCEEDED: &'static str = "CLOCK_SKEW_EXCEEDED";
+    /// The schema for the envelope could not be loaded.
+    pub const SCHEMA_UNAVAILABLE: &'static str = "SCHEMA_UNAVAILABLE";
+    /// The schema itself is malformed.
+    pub const INVALID_SCHEMA: &'static str = "INVALID_SCHEMA";
+    /// A `$ref` could not be resolved.
+    pub const REF_UNRESOLVED: &'static str = "REF_UNRESOLVED";
+    /// A value has the wrong type.
+    pub const TYPE_MISMATCH: &'static str = "TYPE_MISMATCH";
+    /// A required field is absent.
+    pub const REQUIRED_MISSING: &'static str = "REQUIRED_MISSING";
+    /// A required field is `null` while null required fields are rejected.
+    pub const REQUIRED_NULL: &'static str = "REQUIRED_NULL";
+    /// A value is not one of the `enum` members.
+    pub const ENUM_MISMATCH: &'static str = "ENUM_MISMATCH";
+    /// A string does not match its `pattern`.
+    pub const PATTERN_MISMATCH: &'static str = "PATTERN_MISMATCH";
+    /// A `pattern` is not a valid regular expression.
+    pub const PATTERN_INVALID: &'static str = "PATTERN_INVALID";
+    /// A string is shorter than `minLength`.
+    pub const MIN_LENGTH: &'static str = "MIN_LENGTH";
+    /// A string is longer than `maxLength`.
+    pub const MAX_LENGTH: &'static str = "MAX_LENGTH";
+    /// An array has an element where `items` is `false`.
+    pub const UNEXPECTED_ITEM: &'static str = "UNEXPECTED_ITEM";
+    /// Data is nested deeper than the maximum depth.
+    pub const MAX_DEPTH_EXCEEDED: &'static str = "MAX_DEPTH_EXCEEDED";
+    /// Object keys are not in sorted order while sorted keys are required.
+    pub const KEYS_NOT_SORTED: &'static str = "KEYS_NOT_SORTED";
+    /// A `readOnly` field is present in write context.
+    pub const READ_ONLY_FIELD: &'static str = "READ_ONLY_FIELD";
+    /// A `writeOnly` field is present in read context.
+    pub const WRITE_ONLY_FIELD: &'static str = "WRITE_ONLY_FIELD";
+    /// A registered custom rule rejected a value.
+    pub const CUSTOM_RULE_FAILED: &'static str = "CUSTOM_RULE_FAILED";
+    /// An `x-custom` rule is not registered.
+    pub const CUSTOM_RULE_UNKNOWN: &'static str = "CUSTOM_RULE_UNKNOWN";
+    /// Further errors were omitted because of the maximum error count.
+    pub const ERRORS_OMITTED: &'static str = "ERRORS_OMITTED";
+    /// Input could not be parsed as JSON.
+    pub const INVALID_JSON: &'static str = "INVALID_JSON";
+    /// A JSON pointer does not resolve to a value.
+    pub const POINTER_NOT_FOUND: &'static str = "POINTER_NOT_FOUND";
+    /// An envelope is older than the one before it in an ordered batch.
+    pub const OUT_OF_ORDER: &'static str = "OUT_OF_ORDER";
+    /// A value could not be serialized to JSON.
+    pub const SERIALIZATION_FAILED: &'static str = "SERIALIZATION_FAILED";
+    /// The error was recorded without a code.
+    pub const UNCLASSIFIED: &'static str = "UNCLASSIFIED";
+
+    /// Creates an error with the given code and message.
+    pub fn new(code: &'static str, message: String) -> Self {
+        Self {
+            code,
+            message,
+            path: String::new(),
+        }
+    }
+
+    /// Sets the JSON pointer to the value the error is about.
+    pub fn with_path(mut self, path: String) -> Self {
+        self.path = path;
+        self
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Maximum number of `$ref` hops followed in a row before giving up: without
+/// descending into the data during validation, or nested while inlining. This
+/// stops self-referencing schemas from expanding forever.
+const MAX_REF_DEPTH: usize = 32;
+
+/// Default limit on how deeply nested data is descended into during validation.
+const DEFAULT_MAX_DEPTH: usize = 128;
+
+const KNOWN_TYPES: [&str; 7] = [
+    "object", "array", "string", "number", "integer", "boolean", "null",
+];
+
+/// JSON Schema keywords enforced during validation.
+const SUPPORTED_KEYWORDS: [&str; 13] = [
+    "type",
+    "required",
+    "properties",
+    "items",
+    "prefixItems",
+    "enum",
+    "pattern",
+    "minLength",
+    "maxLength",
+    "$ref",
+    "x-custom",
+    "readOnly",
+    "writeOnly",
+];
+
+/// The direction data is flowing in, deciding whether `readOnly` and
+/// `writeOnly` properties may be present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationContext {
+    /// Data returned to a client; `writeOnly` properties are rejected.
+    Read,
+    /// Data submitted by a client; `readOnly` properties are rejected.
+    Write,
+    /// `readOnly` and `writeOnly` are ignored.
+    #[default]
+    Neutral,
 }
 
+/// A validation rule registered with [`Validator::register_custom_rule`]. It
+/// receives the data node and returns an error message if the node is invalid.
+pub type CustomRule = dyn Fn(&Value) -> Result<(), String> + Send + Sync;
+
 /// Validates data against schemas.
 #[derive(Clone)]
 pub struct Validator {
-    schema_loader: std::cell::RefCell<SchemaLoader>,
+    schema_loader: Option<Arc<Mutex<SchemaLoader>>>,
+    fail_fast: bool,
+    lenient_integers: bool,
+    float_enum_epsilon: Option<f64>,
+    max_errors: Option<usize>,
+    error_limit: Option<usize>,
+    max_depth: usize,
+    case_insensitive_keys: bool,
+    coerce_types: bool,
+    reject_null_required: bool,
+    ignore_required: bool,
+    context: ValidationContext,
+    require_sorted_keys: bool,
+    max_clock_skew: Option<Duration>,
+    content_type_schemas: HashMap<String, (String, String)>,
+    custom_rules: HashMap<String, Arc<CustomRule>>,
+    regex_cache: Arc<Mutex<HashMap<String, Regex>>>,
+    #[cfg(feature = "remote")]
+    allowed_ref_hosts: Vec<String>,
+    #[cfg(feature = "remote")]
+    ref_document_cache: Arc<Mutex<HashMap<String, Arc<Value>>>>,
 }
 
 impl Validator {
     /// Creates a new validator with the given schema loader.
     pub fn new(schema_loader: SchemaLoader) -> Self {
+        Self::with_shared_loader(Arc::new(Mutex::new(schema_loader)))
+    }
+
+    /// Creates a new validator that loads schemas through a loader shared with
+    /// other owners, such as [`crate::PactsService`]. Clones of a validator also
+    /// share its loader, so schemas cached by one are seen by all.
+    pub fn with_shared_loader(schema_loader: Arc<Mutex<SchemaLoader>>) -> Self {
         Self {
-            schema_loader: std::cell::RefCell::new(schema_loader),
+            schema_loader: Some(schema_loader),
+            ..Self::standalone()
         }
     }
 
-    /// Validates an envelope against its schema.
-    pub fn validate(&mut self, envelope: &Envelope) -> ValidationResult {
-        let mut errors = Vec::new();
+    /// Creates a validator without a schema loader, for validating data against
+    /// schemas supplied directly to [`Validator::validate_data`].
+    pub fn standalone() -> Self {
+        Self {
+            schema_loader: None,
+            fail_fast: false,
+            lenient_integers: false,
+            float_enum_epsilon: None,
+            max_errors: None,
+            error_limit: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            case_insensitive_keys: false,
+            coerce_types: false,
+            reject_null_required: false,
+            ignore_required: false,
+            context: ValidationContext::Neutral,
+            require_sorted_keys: false,
+            max_clock_skew: None,
+            content_type_schemas: HashMap::new(),
+            custom_rules: HashMap::new(),
+            regex_cache: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "remote")]
+            allowed_ref_hosts: Vec::new(),
+            #[cfg(feature = "remote")]
+            ref_document_cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Enables checking that header timestamps are within `max_skew` of the current time.
+    pub fn with_max_clock_skew(mut self, max_skew: Duration) -> Self {
+        self.max_clock_skew = Some(max_skew);
+        self
+    }
+
+    /// Returns the maximum allowed clock skew for header timestamps, if checked.
+    pub fn get_max_clock_skew(&self) -> Option<Duration> {
+        self.max_clock_skew
+    }
+
+    /// Sets whether validation stops at the first error instead of collecting all errors.
+    pub fn set_fail_fast(&mut self, fail_fast: bool) {
+        self.fail_fast = fail_fast;
+    }
+
+    /// Returns true if validation stops at the first error.
+    pub fn is_fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    /// Sets whether whole-valued numbers such as `3.0` satisfy the `integer` type.
+    pub fn set_lenient_integers(&mut self, lenient_integers: bool) {
+        self.lenient_integers = lenient_integers;
+    }
+
+    /// Returns true if whole-valued numbers satisfy the `integer` type.
+    pub fn is_lenient_integers(&self) -> bool {
+        self.lenient_integers
+    }
+
+    /// Sets the tolerance used when comparing numbers to numeric `enum` members,
+    /// so e.g. `0.30000000000000004` matches `0.3`. Comparison is exact by default.
+    pub fn set_float_enum_epsilon(&mut self, epsilon: f64) {
+        self.float_enum_epsilon = Some(epsilon);
+    }
+
+    /// Returns the tolerance used for numeric `enum` members, if set.
+    pub fn get_float_enum_epsilon(&self) -> Option<f64> {
+        self.float_enum_epsilon
+    }
+
+    /// Caps the number of errors reported; any beyond the cap are summarized in a
+    /// final `... and N more errors` entry.
+    pub fn set_max_errors(&mut self, max_errors: usize) {
+        self.max_errors = Some(max_errors);
+    }
+
+    /// Returns the maximum number of errors reported, if capped.
+    pub fn get_max_errors(&self) -> Option<usize> {
+        self.max_errors
+    }
+
+    /// Sets how many levels of nested objects and arrays are descended into.
+    /// Deeper data is reported as `Maximum nesting depth N exceeded` and not
+    /// validated further. Defaults to 128.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Returns the maximum nesting depth descended into during validation.
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Sets whether `required` and `properties` match data keys regardless of case,
+    /// so `Name` satisfies a schema's `name`. Defaults to case-sensitive.
+    pub fn set_case_insensitive_keys(&mut self, case_insensitive_keys: bool) {
+        self.case_insensitive_keys = case_insensitive_keys;
+    }
+
+    /// Returns true if data keys are matched to schema keys regardless of case.
+    pub fn is_case_insensitive_keys(&self) -> bool {
+        self.case_insensitive_keys
+    }
+
+    /// Sets the context data is validated in. In [`ValidationContext::Write`],
+    /// properties marked `readOnly` must be absent, and in
+    /// [`ValidationContext::Read`], properties marked `writeOnly` must be absent.
+    /// Defaults to [`ValidationContext::Neutral`], which ignores both keywords.
+    pub fn set_context(&mut self, context: ValidationContext) {
+        self.context = context;
+    }
 
-        if envelope.header.schema_category.is_empty()
-            && envelope.header.schema_name.is_empty()
-            && envelope.header.schema_version.is_empty()
+    /// Returns the context data is validated in.
+    pub fn get_context(&self) -> ValidationContext {
+        self.context
+    }
+
+    /// Sets whether [`Validator::validate_and_coerce`] and [`Validator::normalize`]
+    /// parse strings where the schema expects a `number`, `integer`, or `boolean`.
+    pub fn set_coerce_types(&mut self, coerce_types: bool) {
+        self.coerce_types = coerce_types;
+    }
+
+    /// Returns true if strings are coerced to the types their schema expects.
+    pub fn is_coerce_types(&self) -> bool {
+        self.coerce_types
+    }
+
+    /// Sets whether a required field that is present but `null` is reported as
+    /// `Required field is null: <name>`. By default an explicit `null` counts as present.
+    pub fn set_reject_null_required(&mut self, reject_null_required: bool) {
+        self.reject_null_required = reject_null_required;
+    }
+
+    /// Returns true if required fields must be non-null.
+    pub fn is_reject_null_required(&self) -> bool {
+        self.reject_null_required
+    }
+
+    /// Sets whether objects in validated data must have lexically sorted keys, as
+    /// produced by canonicalization. Keys are checked in the order they were
+    /// parsed in.
+    pub fn set_require_sorted_keys(&mut self, require_sorted_keys: bool) {
+        self.require_sorted_keys = require_sorted_keys;
+    }
+
+    /// Returns true if objects in validated data must have sorted keys.
+    pub fn is_require_sorted_keys(&self) -> bool {
+        self.require_sorted_keys
+    }
+
+    /// Drops every compiled `pattern` regex. The cache is shared with clones of
+    /// this validator.
+    pub fn clear_regex_cache(&self) {
+        self.regex_cache.lock().unwrap().clear();
+    }
+
+    /// Returns the number of compiled `pattern` regexes in the cache.
+    pub fn regex_cache_len(&self) -> usize {
+        self.regex_cache.lock().unwrap().len()
+    }
+
+    /// Sets the hosts external `$ref` documents may be fetched from; all others
+    /// are rejected. No hosts are allowed by default.
+    #[cfg(feature = "remote")]
+    pub fn set_allowed_ref_hosts(&mut self, hosts: Vec<String>) {
+        self.allowed_ref_hosts = hosts;
+    }
+
+    /// Returns the hosts external `$ref` documents may be fetched from.
+    #[cfg(feature = "remote")]
+    pub fn get_allowed_ref_hosts(&self) -> &[String] {
+        &self.allowed_ref_hosts
+    }
+
+    /// Registers a rule run on every data node whose schema contains
+    /// `"x-custom": name`, for checks JSON Schema cannot express, such as one
+    /// date preceding another. The error the rule returns is reported as is.
+    pub fn register_custom_rule(&mut self, name: &str, rule: Box<CustomRule>) {
+        self.custom_rules.insert(name.to_string(), Arc::from(rule));
+    }
+
+    /// Registers the schema used to validate envelopes with the given content type,
+    /// overriding the schema category and name in their headers.
+    pub fn register_content_type_schema(
+        &mut self,
+        content_type: String,
+        category: String,
+        name: String,
+    ) {
+        self.content_type_schemas
+            .insert(content_type, (category, name));
+    }
+
+    /// Returns the schema category and name used to validate an envelope with the
+    /// given header: the registered content type schema if any, otherwise the header's.
+    pub fn resolve_schema<'a>(&'a self, header: &'a Header) -> Option<(&'a str, &'a str)> {
+        if let Some((category, name)) = header
+            .content_type()
+            .and_then(|content_type| self.content_type_schemas.get(content_type))
         {
-            errors.push("Header is required".to_string());
-            return ValidationResult::new(false, errors);
+            return Some((category.as_str(), name.as_str()));
+        }
+
+        if header.schema_category().is_empty() || header.schema_name().is_empty() {
+            return None;
         }
 
-        if envelope.header.schema_category.is_empty() {
-            errors.push("Schema category is required in header".to_string());
+        Some((header.schema_category(), header.schema_name()))
+    }
+
+    /// Checks that a header names a schema category, name, and version, without
+    /// touching the envelope body. Useful as a cheap pre-check.
+    pub fn validate_header(&self, header: &Header) -> ValidationResult {
+        ValidationResult::from_errors(header_errors(header))
+    }
+
+    /// Validates an envelope against its schema, loaded for the version in the
+    /// envelope's header.
+    pub fn validate(&self, envelope: &Envelope) -> ValidationResult {
+        self.validate_with_schema(envelope, self.load_envelope_schema(envelope).as_ref())
+    }
+
+    /// Loads the schema an envelope is validated against, as chosen by
+    /// [`Validator::resolve_schema`], for the version in its header. Returns `None`
+    /// if the header does not name a schema.
+    pub(crate) fn load_envelope_schema(
+        &self,
+        envelope: &Envelope,
+    ) -> Option<Result<Arc<Value>, ValidationError>> {
+        let version = envelope.header.schema_version();
+        let (category, name) = self
+            .resolve_schema(&envelope.header)
+            .filter(|_| !version.is_empty())?;
+
+        Some(match &self.schema_loader {
+            Some(schema_loader) => schema_loader
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .load_schema_version(version, category, name)
+                .map_err(|e| {
+                    ValidationError::new(ValidationError::SCHEMA_UNAVAILABLE, e.to_string())
+                }),
+            None => Err(ValidationError::new(
+                ValidationError::SCHEMA_UNAVAILABLE,
+                "No schema loader configured".to_string(),
+            )),
+        })
+    }
+
+    /// Validates an envelope against a schema already loaded with
+    /// [`Validator::load_envelope_schema`].
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                category = %envelope.header.schema_category,
+                name = %envelope.header.schema_name,
+                error_count = tracing::field::Empty,
+            )
+        )
+    )]
+    pub(crate) fn validate_with_schema(
+        &self,
+        envelope: &Envelope,
+        schema: Option<&Result<Arc<Value>, ValidationError>>,
+    ) -> ValidationResult {
+        let mut errors = header_errors(&envelope.header);
+        if header_is_missing(&envelope.header) {
+            return record_outcome(ValidationResult::from_errors(errors));
         }
 
-        if envelope.header.schema_name.is_empty() {
-            errors.push("Schema name is required in header".to_string());
+        if let Some(expires_at) = envelope.header.expires_at() {
+            if *expires_at < Utc::now() {
+                errors.push(ValidationError::new(
+                    ValidationError::ENVELOPE_EXPIRED,
+                    format!("Envelope expired at {}", expires_at.to_rfc3339()),
+                ));
+            }
         }
 
-        if envelope.header.schema_version.is_empty() {
-            errors.push("Schema version is required in header".to_string());
+        if let Some(max_skew) = self.max_clock_skew {
+            let skew = *envelope.header.timestamp() - Utc::now();
+            if skew > max_skew {
+                errors.push(ValidationError::new(
+                    ValidationError::CLOCK_SKEW_EXCEEDED,
+                    format!(
+                        "Header timestamp is {} in the future (max skew {})",
+                        format_duration(skew),
+                        format_duration(max_skew)
+                    ),
+                ));
+            } else if -skew > max_skew {
+                errors.push(ValidationError::new(
+                    ValidationError::CLOCK_SKEW_EXCEEDED,
+                    format!(
+                        "Header timestamp is {} in the past (max skew {})",
+                        format_duration(-skew),
+                        format_duration(max_skew)
+                    ),
+                ));
+            }
         }
 
-        if !envelope.header.schema_category.is_empty() && !envelope.header.schema_name.is_empty() {
-            let schema = self.schema_loader.borrow_mut().load_schema(
-                &envelope.header.schema_category,
-                &envelope.header.schema_name,
-            );
-            let data_validation = self.validate_data(&envelope.data, &schema);
-            errors.extend(data_validation.get_errors().to_vec());
+        match schema {
+            Some(Ok(schema)) => errors.extend(self.data_errors(&envelope.data, schema)),
+            Some(Err(e)) => errors.push(e.clone()),
+            None => {}
         }
 
-        ValidationResult::new(errors.is_empty(), errors)
+        record_outcome(self.capped_result(errors))
     }
 
     /// Validates data against a schema.
+    ///
+    /// A top-level string payload is only checked against `type`, `enum`,
+    /// `pattern`, `minLength`, `maxLength` and `x-custom`, so large opaque strings
+    /// are never traversed as objects.
+    ///
+    /// Properties are checked in order of their names, so errors are reported in
+    /// the same order regardless of how the schema orders its keys.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(error_count = tracing::field::Empty))
+    )]
     pub fn validate_data(&self, data: &Value, schema: &Value) -> ValidationResult {
-        let mut errors = Vec::new();
+        record_outcome(self.capped_result(self.data_errors(data, schema)))
+    }
 
-        self.validate_required_fields(data, schema, &mut errors);
-        self.validate_type_schema(data, schema, &mut errors);
-        self.validate_properties(data, schema, &mut errors);
+    /// Validates only the part of `data` at the JSON pointer `pointer` (e.g.
+    /// `/payload`) against a schema. A pointer that does not resolve is reported
+    /// as `No value at pointer <pointer>`.
+    pub fn validate_at_pointer(
+        &self,
+        data: &Value,
+        pointer: &str,
+        schema: &Value,
+    ) -> ValidationResult {
+        match data.pointer(pointer) {
+            Some(subtree) => self.validate_data(subtree, schema),
+            None => ValidationResult::from_errors(vec![ValidationError::new(
+                ValidationError::POINTER_NOT_FOUND,
+                format!("No value at pointer {}", pointer),
+            )]),
+        }
+    }
 
-        ValidationResult::new(errors.is_empty(), errors)
+    /// Serializes `value` to JSON and validates it against a schema. A value that
+    /// cannot be serialized is reported as a validation failure.
+    pub fn validate_value_of<T: Serialize>(&self, value: &T, schema: &Value) -> ValidationResult {
+        match serde_json::to_value(value) {
+            Ok(data) => self.validate_data(&data, schema),
+            Err(e) => ValidationResult::from_errors(vec![ValidationError::new(
+                ValidationError::SERIALIZATION_FAILED,
+                format!("Failed to serialize value: {}", e),
+            )]),
+        }
     }
 
-    fn validate_type(&self, data: &Value, expected_type: &str) -> bool {
-        match expected_type {
-            "object" => data.is_object(),
-            "array" => data.is_array(),
-            "string" => data.is_string(),
-            "number" => data.is_number(),
-            "integer" => data.is_i64() || data.is_u64(),
-            "boolean" => data.is_boolean(),
-            "null" => data.is_null(),
-            _ => true,
+    /// Validates data against a schema, stopping once `limit` errors have been
+    /// found. Nested properties and array items are not visited after that, and
+    /// the result is marked truncated when the limit was reached.
+    pub fn validate_data_limited(
+        &self,
+        data: &Value,
+        schema: &Value,
+        limit: usize,
+    ) -> ValidationResult {
+        let limit = limit.max(1);
+        let mut limited = self.data_only();
+        limited.max_errors = None;
+        limited.error_limit = Some(limit);
+
+        let mut errors = limited.data_errors(data, schema);
+        let truncated = errors.len() >= limit;
+        errors.truncate(limit);
+
+        let mut result = ValidationResult::from_errors(errors);
+        result.truncated = truncated;
+        result
+    }
+
+    /// Validates data against a schema for a partial update: types, enums,
+    /// patterns, lengths, and items of the fields present are checked, but
+    /// `required` is ignored at every level.
+    pub fn validate_data_partial(&self, data: &Value, schema: &Value) -> ValidationResult {
+        let mut partial = self.data_only();
+        partial.ignore_required = true;
+        partial.capped_result(partial.data_errors(data, schema))
+    }
+
+    /// Validates each element of a top-level JSON array read from `reader`
+    /// against `item_schema`, yielding the element's index and result as it is
+    /// read. Only one element is held in memory at a time. An element that is not
+    /// valid JSON yields a failure; a stream that is not an array, or that ends
+    /// early, yields a failure and ends the iteration. An item schema that does
+    /// not compile yields a single failure without reading the stream.
+    pub fn validate_stream<'a, R: Read + 'a>(
+        &'a self,
+        reader: R,
+        item_schema: &Value,
+    ) -> impl Iterator<Item = (usize, ValidationResult)> + 'a {
+        let (compiled, compile_failure) = match self.compile(item_schema) {
+            Ok(compiled) => (Some(compiled), None),
+            Err(e) => (None, Some((0, ValidationResult::from_errors(vec![e])))),
+        };
+        let results = compiled.map(|compiled| {
+            ArrayElements::new(reader)
+                .enumerate()
+                .map(move |(index, element)| {
+                    let result = match element {
+                        Ok(element) => self.validate_compiled(&element, &compiled),
+                        Err(e) => ValidationResult::from_errors(vec![ValidationError::new(
+                            ValidationError::INVALID_JSON,
+                            e,
+                        )]),
+                    };
+                    (index, result)
+                })
+        });
+        compile_failure
+            .into_iter()
+            .chain(results.into_iter().flatten())
+    }
+
+    /// Lists the changes from `old` to `new` that can reject data the old schema
+    /// accepted: newly required fields, removed properties, narrowed types, and
+    /// added or tightened enums. An empty list means the new schema is backward
+    /// compatible. Properties and items present in both schemas are compared
+    /// recursively; `$ref`s are not followed.
+    pub fn is_backward_compatible(old: &Value, new: &Value) -> Vec<Incompatibility> {
+        compatibility::compare(old, new)
+    }
+
+    /// Validates data against a schema and groups the error messages by the
+    /// JSON pointer to the value they are about, with the field and index removed
+    /// from each message, e.g. `{"/age": ["Invalid type. Expected: integer"]}`.
+    /// Errors about the data as a whole are grouped under the empty key.
+    pub fn validate_data_grouped(
+        &self,
+        data: &Value,
+        schema: &Value,
+    ) -> HashMap<String, Vec<String>> {
+        let mut grouped: HashMap<String, Vec<String>> = HashMap::new();
+        for error in self.data_errors(data, schema) {
+            grouped
+                .entry(error.path)
+                .or_default()
+                .push(bare_message(&error.message));
         }
+        grouped
     }
 
-    fn validate_required_fields(&self, data: &Value, schema: &Value, errors: &mut Vec<String>) {
-        if let Some(required_fields) = schema.get("required") {
-            if let Some(required_array) = required_fields.as_array() {
-                for field in required_array {
-                    if let Some(field_name) = field.as_str() {
-                        if !data.get(field_name).is_some() {
-                            errors.push(format!("Required field missing: {}", field_name));
-                        }
-                    }
-                }
+    /// Copies the data validation options into a validator without a schema
+    /// loader, sharing the regex and `$ref` caches.
+    fn data_only(&self) -> Validator {
+        Validator {
+            schema_loader: None,
+            fail_fast: self.fail_fast,
+            lenient_integers: self.lenient_integers,
+            float_enum_epsilon: self.float_enum_epsilon,
+            max_errors: self.max_errors,
+            error_limit: self.error_limit,
+            max_depth: self.max_depth,
+            case_insensitive_keys: self.case_insensitive_keys,
+            coerce_types: self.coerce_types,
+            reject_null_required: self.reject_null_required,
+            ignore_required: self.ignore_required,
+            context: self.context,
+            require_sorted_keys: self.require_sorted_keys,
+            max_clock_skew: self.max_clock_skew,
+            content_type_schemas: HashMap::new(),
+            custom_rules: self.custom_rules.clone(),
+            regex_cache: Arc::clone(&self.regex_cache),
+            #[cfg(feature = "remote")]
+            allowed_ref_hosts: self.allowed_ref_hosts.clone(),
+            #[cfg(feature = "remote")]
+            ref_document_cache: Arc::clone(&self.ref_document_cache),
+        }
+    }
+
+    fn data_errors(&self, data: &Value, schema: &Value) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        self.with_resolved(
+            schema,
+            schema,
+            0,
+            &mut errors,
+            &mut |schema, root, errors| self.validate_root(data, schema, root, errors),
+        );
+        errors
+    }
+
+    /// Validates the top-level data against its (already resolved) schema.
+    fn validate_root(
+        &self,
+        data: &Value,
+        schema: &Value,
+        root: &Value,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if data.is_string() {
+            self.validate_type_schema(data, schema, errors);
+            if !self.should_stop(errors) {
+                self.validate_enum(data, schema_enum(schema), None, None, errors);
+            }
+            if !self.should_stop(errors) {
+                self.validate_pattern(data, schema_pattern(schema), None, None, errors);
+            }
+            if !self.should_stop(errors) {
+                self.validate_string_length(
+                    data,
+                    schema_length(schema, "minLength"),
+                    schema_length(schema, "maxLength"),
+                    None,
+                    None,
+                    errors,
+                );
+            }
+            if !self.should_stop(errors) {
+                self.validate_custom_rule(data, schema_custom_rule(schema), errors);
             }
+            return;
+        }
+
+        self.validate_required_fields(data, schema, errors);
+        if !self.should_stop(errors) {
+            self.validate_type_schema(data, schema, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_enum(data, schema_enum(schema), None, None, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_sorted_keys(data, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_properties(data, schema, root, 0, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_items(data, schema, root, None, 0, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_custom_rule(data, schema_custom_rule(schema), errors);
         }
     }
 
-    fn validate_type_schema(&self, data: &Value, schema: &Value, errors: &mut Vec<String>) {
-        if let Some(type_value) = schema.get("type") {
-            if let Some(expected_type) = type_value.as_str() {
+    /// Compiles a schema for repeated validation with [`Validator::validate_compiled`].
+    /// A `$ref` that cannot be resolved or a `pattern` that is not a valid regular
+    /// expression is reported here rather than on every validation.
+    pub fn compile(&self, schema: &Value) -> Result<CompiledSchema, ValidationError> {
+        CompiledSchema::compile_with(schema, &|url| self.ref_document(url))
+    }
+
+    /// Validates data against a compiled schema, producing the same result as
+    /// [`Validator::validate_data`] with the original schema.
+    pub fn validate_compiled(&self, data: &Value, compiled: &CompiledSchema) -> ValidationResult {
+        let refs = compiled.refs.as_slice();
+        let compiled = compiled.resolve(refs);
+        let mut errors = Vec::new();
+
+        if data.is_string() {
+            if let Some(expected_type) = compiled.schema_type() {
                 if !self.validate_type(data, expected_type) {
-                    errors.push(format!("Invalid type. Expected: {}", expected_type));
+                    errors.push(ValidationError::new(
+                        ValidationError::TYPE_MISMATCH,
+                        format!(
+                            "Invalid type. Expected: {}{}",
+                            expected_type,
+                            description_suffix(compiled.description.as_deref())
+                        ),
+                    ));
                 }
             }
+            if !self.should_stop(&errors) {
+                self.validate_enum(
+                    data,
+                    compiled.enum_values.as_deref(),
+                    None,
+                    None,
+                    &mut errors,
+                );
+            }
+            if !self.should_stop(&errors) {
+                self.validate_compiled_pattern(
+                    data,
+                    compiled.pattern.as_deref(),
+                    None,
+                    None,
+                    &mut errors,
+                );
+            }
+            if !self.should_stop(&errors) {
+                self.validate_string_length(
+                    data,
+                    compiled.min_length,
+                    compiled.max_length,
+                    None,
+                    None,
+                    &mut errors,
+                );
+            }
+            if !self.should_stop(&errors) {
+                self.validate_custom_rule(data, compiled.custom_rule.as_deref(), &mut errors);
+            }
+            return self.capped_result(errors);
         }
-    }
 
-    fn validate_property_type(
-        &self,
-        data: &Value,
-        property_name: &str,
-        property_schema: &Value,
-        errors: &mut Vec<String>,
-    ) {
-        if let Some(property_type) = property_schema.get("type") {
-            if let Some(expected_type) = property_type.as_str() {
-                if let Some(property_value) = data.get(property_name) {
-                    if !self.validate_type(property_value, expected_type) {
-                        errors.push(format!(
-                            "Invalid type for field '{}'. Expected: {}",
-                            property_name, expected_type
-                        ));
-                    }
+        self.validate_compiled_required(data, compiled, &mut errors);
+        if !self.should_stop(&errors) {
+            if let Some(expected_type) = compiled.schema_type() {
+                if !self.validate_type(data, expected_type) {
+                    errors.push(ValidationError::new(
+                        ValidationError::TYPE_MISMATCH,
+                        format!(
+                            "Invalid type. Expected: {}{}",
+                            expected_type,
+                            description_suffix(compiled.description.as_deref())
+                        ),
+                    ));
                 }
             }
         }
+        if !self.should_stop(&errors) {
+            self.validate_enum(
+                data,
+                compiled.enum_values.as_deref(),
+                None,
+                None,
+                &mut errors,
+            );
+        }
+        if !self.should_stop(&errors) {
+            self.validate_sorted_keys(data, &mut errors);
+        }
+        if !self.should_stop(&errors) {
+            self.validate_compiled_properties(data, compiled, refs, 0, &mut errors);
+        }
+        if !self.should_stop(&errors) {
+            self.validate_compiled_items(data, compiled, refs, None, 0, &mut errors);
+        }
+        if !self.should_stop(&errors) {
+            self.validate_custom_rule(data, compiled.custom_rule.as_deref(), &mut errors);
+        }
+
+        self.capped_result(errors)
     }
 
-    fn validate_properties(&self, data: &Value, schema: &Value, errors: &mut Vec<String>) {
-        if let Some(properties) = schema.get("properties") {
-            if data.is_object() && properties.is_object() {
-                if let Some(properties_obj) = properties.as_object() {
-                    for (property_name, property_schema) in properties_obj {
-                        if data.get(property_name).is_some() {
-                            self.validate_property_type(
-                                data,
-                                property_name,
-                                property_schema,
-                                errors,
-                            );
+    /// Returns a copy of `data` with each missing property set to its schema
+    /// `default`. With lenient integers enabled, integral floats in `integer`
+    /// fields that fit in 64 bits are also converted to integers, and with type
+    /// coercion enabled, strings in `number`, `integer`, and `boolean` fields
+    /// are parsed.
+    pub fn normalize(&self, data: &Value, schema: &Value) -> Value {
+        let mut normalized = data.clone();
+        self.normalize_value(&mut normalized, schema, true);
+        normalized
+    }
+
+    /// Rewrites strings such as `"42"` or `"true"` where the schema expects a
+    /// `number`, `integer`, or `boolean` into that type, when type coercion is
+    /// enabled, then validates the rewritten data. Strings that do not parse are
+    /// left as they are and reported by validation.
+    pub fn validate_and_coerce(&self, data: &mut Value, schema: &Value) -> ValidationResult {
+        if self.coerce_types {
+            self.normalize_value(data, schema, false);
+        }
+        self.validate_data(data, schema)
+    }
+
+    /// Returns the JSON Schema keywords this validator enforces. Keywords not in
+    /// this list are ignored during validation.
+    pub fn supported_keywords() -> &'static [&'static str] {
+        &SUPPORTED_KEYWORDS
+    }
+
+    /// Checks that a schema is structurally sane: the schema and its nested
+    /// property and item schemas are objects, `type` names known types,
+    /// `required` is an array of unique strings, `properties` is an object,
+    /// `enum` is an array, and `pattern` is a valid regular expression.
+    pub fn validate_schema(&self, schema: &Value) -> ValidationResult {
+        ValidationResult::from_errors(
+            schema_errors(schema)
+                .into_iter()
+                .map(|e| ValidationError::new(ValidationError::INVALID_SCHEMA, e))
+                .collect(),
+        )
+    }
+
+    /// Checks that a schema document is well-formed. Same as [`Validator::validate_schema`].
+    pub fn validate_schema_document(&self, schema: &Value) -> ValidationResult {
+        self.validate_schema(schema)
+    }
+
+    fn should_stop(&self, errors: &[ValidationError]) -> bool {
+        (self.fail_fast && !errors.is_empty())
+            || self.error_limit.is_some_and(|limit| errors.len() >= limit)
+    }
+
+    fn normalize_value(&self, data: &mut Value, schema: &Value, fill_defaults: bool) {
+        if self.coerce_types {
+            if let (Some(text), Some(expected_type)) =
+                (data.as_str(), schema.get("type").and_then(|t| t.as_str()))
+            {
+                if let Some(coerced) = coerce_string(text, expected_type) {
+                    *data = coerced;
+                }
+            }
+        }
+
+        if self.lenient_integers
+            && !data.is_i64()
+            && !data.is_u64()
+            && schema.get("type").and_then(|t| t.as_str()) == Some("integer")
+        {
+            if let Some(integer) = data.as_f64().and_then(integer_value) {
+                *data = integer;
+            }
+        }
+
+        if let (Some(object), Some(properties)) = (
+            data.as_object_mut(),
+            schema.get("properties").and_then(|p| p.as_object()),
+        ) {
+            for (property_name, property_schema) in properties {
+                match object.get_mut(property_name) {
+                    Some(value) => self.normalize_value(value, property_schema, fill_defaults),
+                    None if fill_defaults => {
+                        if let Some(default) = property_schema.get("default") {
+                            object.insert(property_name.clone(), default.clone());
                         }
                     }
+                    None => {}
                 }
             }
         }
+
+        if let (Some(elements), Some(item_schema)) = (data.as_array_mut(), schema.get("items")) {
+            for element in elements {
+                self.normalize_value(element, item_schema, fill_defaults);
+            }
+        }
+    }
+
+    fn validate_type(&self, data: &Value, expected_type: &str) -> bool {
+        match expected_type {
+            "object" => data.is_object(),
+            "array" => data.is_array(),
+            "string" => data.is_string(),
+            "number" => data.is_number(),
+            "integer" => {
+                data.is_i64()
+                    || data.is_u64()
+                    || (self.lenient_integers
+                        && data.as_f64().map(|n| n.fract() == 0.0).unwrap_or(false))
+            }
+            "boolean" => data.is_boolean(),
+            "null" => data.is_null(),
+            _ => true,
+        }
+    }
+
+    /// Looks up a field of an object, ignoring case if `case_insensitive_keys` is
+    /// set. An exact match wins over a case-insensitive one.
+    fn get_field<'a>(&self, data: &'a Value, key: &str) -> Option<&'a Value> {
+        let exact = data.get(key);
+        if exact.is_some() || !self.case_insensitive_keys {
+            return exact;
+        }
+
+        let key = key.to_lowercase();
+        data.as_object()?
+            .iter()
+            .find(|(candidate, _)| candidate.to_lowercase() == key)
+            .map(|(_, value)| value)
+    }
+
+    fn check_required_field(
+        &self,
+        data: &Value,
+        field_name: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        match self.get_field(data, field_name) {
+            None => errors.push(
+                ValidationError::new(
+                    ValidationError::REQUIRED_MISSING,
+                    format!("Required field missing: {}", field_name),
+                )
+                .with_path(pointer_to(field_name)),
+            ),
+            Some(Value::Null) if self.reject_null_required => errors.push(
+                ValidationError::new(
+                    ValidationError::REQUIRED_NULL,
+                    format!("Required field is null: {}", field_name),
+                )
+                .with_path(pointer_to(field_name)),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    fn validate_required_fields(
+        &self,
+        data: &Value,
+        schema: &Value,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if self.ignore_required {
+            return;
+        }
+        if let Some(required_fields) = schema.get("required") {
+            if let Some(required_array) = required_fields.as_array() {
+                for field in required_array {
+                    if self.should_stop(errors) {
+                        return;
+                    }
+                    if let Some(field_name) = field.as_str() {
+                        self.check_required_field(data, field_name, errors);
+                    }
+                }
+            }
+        }
+    }
+
+    /// String lengths are counted in characters (Unicode scalar values), as
+    /// JSON Schema specifies, not in UTF-8 bytes. Values that are not strings
+    /// are ignored.
+    fn validate_string_length(
+        &self,
+        value: &Value,
+        min_length: Option<u64>,
+        max_length: Option<u64>,
+        field: Option<&str>,
+        index: Option<usize>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let text = match value.as_str() {
+            Some(text) if min_length.is_some() || max_length.is_some() => text,
+            _ => return,
+        };
+
+        let length = text.chars().count() as u64;
+
+        if let Some(max_length) = max_length {
+            if length > max_length {
+                errors.push(ValidationError::new(
+                    ValidationError::MAX_LENGTH,
+                    format!(
+                        "String length {}{} exceeds maxLength {}",
+                        length,
+                        location_label(field, index),
+                        max_length
+                    ),
+                ));
+            }
+        }
+
+        if let Some(min_length) = min_length {
+            if length < min_length {
+                errors.push(ValidationError::new(
+                    ValidationError::MIN_LENGTH,
+                    format!(
+                        "String length {}{} is below minLength {}",
+                        length,
+                        location_label(field, index),
+                        min_length
+                    ),
+                ));
+            }
+        }
+    }
+
+    /// Calls `validate` with the schema `schema` stands for and the document its
+    /// own `$ref`s resolve against. Fragment-only references such as
+    /// `#/definitions/address` or `#/$defs/address` resolve against `root`;
+    /// absolute URLs are fetched from allowed hosts with the `remote` feature and
+    /// resolved within the fetched document. References are followed only when
+    /// validation reaches them, so recursive schemas are bounded by the depth of
+    /// the data; `hops` counts references followed in a row without descending,
+    /// which stops `$ref` loops. A reference that does not resolve is reported
+    /// as an error.
+    fn with_resolved(
+        &self,
+        schema: &Value,
+        root: &Value,
+        hops: usize,
+        errors: &mut Vec<ValidationError>,
+        validate: &mut dyn FnMut(&Value, &Value, &mut Vec<ValidationError>),
+    ) {
+        let reference = match schema.get("$ref").and_then(|r| r.as_str()) {
+            Some(reference) => reference,
+            None => return validate(schema, root, errors),
+        };
+        let unresolved =
+            |message: String| ValidationError::new(ValidationError::REF_UNRESOLVED, message);
+        if hops >= MAX_REF_DEPTH {
+            errors.push(unresolved(format!(
+                "$ref nesting too deep at: {}",
+                reference
+            )));
+            return;
+        }
+
+        let (url, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let document;
+        let root = if url.is_empty() {
+            root
+        } else {
+            match self.ref_document(url) {
+                Ok(fetched) => {
+                    document = fetched;
+                    &*document
+                }
+                Err(e) => return errors.push(unresolved(e)),
+            }
+        };
+        match root.pointer(fragment) {
+            Some(target) => self.with_resolved(target, root, hops + 1, errors, validate),
+            None => errors.push(unresolved(format!("Unresolved $ref: {}", reference))),
+        }
+    }
+
+    /// Returns a standalone copy of a schema with every `$ref` replaced by the
+    /// subschema it points to. A `$ref` back into a schema that is already being
+    /// inlined is replaced with `{"$circularRef": "<ref>"}`, and the root `$defs`
+    /// and `definitions` are dropped since nothing refers to them any more.
+    pub fn inline_refs(&self, schema: &Value) -> Result<Value, String> {
+        let mut inlined = self.inline_node(schema, schema, &mut Vec::new())?;
+        if let Some(object) = inlined.as_object_mut() {
+            object.shift_remove("$defs");
+            object.shift_remove("definitions");
+        }
+        Ok(inlined)
+    }
+
+    fn inline_node(
+        &self,
+        schema: &Value,
+        root: &Value,
+        expanding: &mut Vec<String>,
+    ) -> Result<Value, String> {
+        match schema {
+            Value::Object(object) => {
+                if let Some(reference) = object.get("$ref").and_then(|r| r.as_str()) {
+                    if expanding.iter().any(|r| r == reference) {
+                        return Ok(serde_json::json!({ "$circularRef": reference }));
+                    }
+                    if expanding.len() >= MAX_REF_DEPTH {
+                        return Err(format!("$ref nesting too deep at: {}", reference));
+                    }
+
+                    let (url, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+                    let document;
+                    let (target, target_root) = if url.is_empty() {
+                        (root.pointer(fragment), root)
+                    } else {
+                        document = self.ref_document(url)?;
+                        (document.pointer(fragment), &*document)
+                    };
+                    let target = target.ok_or_else(|| format!("Unresolved $ref: {}", reference))?;
+
+                    expanding.push(reference.to_string());
+                    let inlined = self.inline_node(target, target_root, expanding);
+                    expanding.pop();
+                    return inlined;
+                }
+
+                object
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok((key.clone(), self.inline_node(value, root, expanding)?))
+                    })
+                    .collect::<Result<serde_json::Map<_, _>, String>>()
+                    .map(Value::Object)
+            }
+            Value::Array(elements) => elements
+                .iter()
+                .map(|element| self.inline_node(element, root, expanding))
+                .collect::<Result<Vec<_>, String>>()
+                .map(Value::Array),
+            _ => Ok(schema.clone()),
+        }
+    }
+
+    #[cfg(feature = "remote")]
+    fn ref_document(&self, url: &str) -> Result<Arc<Value>, String> {
+        if let Some(document) = self.ref_document_cache.lock().unwrap().get(url) {
+            return Ok(Arc::clone(document));
+        }
+
+        let document = Arc::new(crate::core::remote::fetch_schema_document(
+            url,
+            &self.allowed_ref_hosts,
+        )?);
+        self.ref_document_cache
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), Arc::clone(&document));
+        Ok(document)
+    }
+
+    #[cfg(not(feature = "remote"))]
+    fn ref_document(&self, url: &str) -> Result<Arc<Value>, String> {
+        Err(format!(
+            "External $ref requires the remote feature: {}",
+            url
+        ))
+    }
+
+    fn capped_result(&self, mut errors: Vec<ValidationError>) -> ValidationResult {
+        let mut truncated = false;
+
+        if let Some(max_errors) = self.max_errors {
+            if errors.len() > max_errors {
+                let omitted = errors.len() - max_errors;
+                errors.truncate(max_errors);
+                errors.push(ValidationError::new(
+                    ValidationError::ERRORS_OMITTED,
+                    format!("... and {} more errors", omitted),
+                ));
+                truncated = true;
+            }
+        }
+
+        let mut result = ValidationResult::from_errors(errors);
+        result.truncated = truncated;
+        result
+    }
+
+    /// Reports, once, that data nested deeper than `max_depth` was not descended into.
+    fn depth_exceeded(&self, depth: usize, errors: &mut Vec<ValidationError>) -> bool {
+        if depth < self.max_depth {
+            return false;
+        }
+        let message = format!("Maximum nesting depth {} exceeded", self.max_depth);
+        if !errors.iter().any(|error| error.message == message) {
+            errors.push(ValidationError::new(
+                ValidationError::MAX_DEPTH_EXCEEDED,
+                message,
+            ));
+        }
+        true
+    }
+
+    fn validate_sorted_keys(&self, data: &Value, errors: &mut Vec<ValidationError>) {
+        if self.require_sorted_keys && !self.keys_sorted(data, 0, errors) {
+            errors.push(ValidationError::new(
+                ValidationError::KEYS_NOT_SORTED,
+                "Object keys are not in canonical (sorted) order".to_string(),
+            ));
+        }
+    }
+
+    /// Returns whether every object in `data` has its keys in sorted order, without
+    /// descending past `max_depth` levels of nested objects and arrays.
+    fn keys_sorted(&self, data: &Value, depth: usize, errors: &mut Vec<ValidationError>) -> bool {
+        let children: Vec<&Value> = match data {
+            Value::Object(object) => {
+                let keys: Vec<&String> = object.keys().collect();
+                if !keys.windows(2).all(|pair| pair[0] <= pair[1]) {
+                    return false;
+                }
+                object.values().collect()
+            }
+            Value::Array(elements) => elements.iter().collect(),
+            _ => return true,
+        };
+        if self.depth_exceeded(depth, errors) {
+            return true;
+        }
+        children
+            .into_iter()
+            .all(|child| self.keys_sorted(child, depth + 1, errors))
+    }
+
+    fn validate_pattern(
+        &self,
+        value: &Value,
+        pattern: Option<&str>,
+        field: Option<&str>,
+        index: Option<usize>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let (text, pattern) = match (value.as_str(), pattern) {
+            (Some(text), Some(pattern)) => (text, pattern),
+            _ => return,
+        };
+
+        let mut regex_cache = self.regex_cache.lock().unwrap();
+        if !regex_cache.contains_key(pattern) {
+            match Regex::new(pattern) {
+                Ok(regex) => {
+                    regex_cache.insert(pattern.to_string(), regex);
+                }
+                Err(_) => {
+                    errors.push(ValidationError::new(
+                        ValidationError::PATTERN_INVALID,
+                        format!(
+                            "Invalid pattern{}: {}",
+                            location_label(field, index),
+                            pattern
+                        ),
+                    ));
+                    return;
+                }
+            }
+        }
+
+        if !regex_cache[pattern].is_match(text) {
+            errors.push(pattern_mismatch(field, index, pattern));
+        }
+    }
+
+    fn validate_compiled_pattern(
+        &self,
+        value: &Value,
+        regex: Option<&Regex>,
+        field: Option<&str>,
+        index: Option<usize>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let (Some(text), Some(regex)) = (value.as_str(), regex) {
+            if !regex.is_match(text) {
+                errors.push(pattern_mismatch(field, index, regex.as_str()));
+            }
+        }
+    }
+
+    fn validate_access(
+        &self,
+        property_name: &str,
+        read_only: bool,
+        write_only: bool,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        match self.context {
+            ValidationContext::Write if read_only => errors.push(ValidationError::new(
+                ValidationError::READ_ONLY_FIELD,
+                format!("Read-only field present: {}", property_name),
+            )),
+            ValidationContext::Read if write_only => errors.push(ValidationError::new(
+                ValidationError::WRITE_ONLY_FIELD,
+                format!("Write-only field present: {}", property_name),
+            )),
+            _ => {}
+        }
+    }
+
+    fn validate_custom_rule(
+        &self,
+        value: &Value,
+        rule: Option<&str>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let name = match rule {
+            Some(name) => name,
+            None => return,
+        };
+
+        match self.custom_rules.get(name) {
+            Some(rule) => {
+                if let Err(e) = rule(value) {
+                    errors.push(ValidationError::new(ValidationError::CUSTOM_RULE_FAILED, e));
+                }
+            }
+            None => errors.push(ValidationError::new(
+                ValidationError::CUSTOM_RULE_UNKNOWN,
+                format!("Unknown custom rule: {}", name),
+            )),
+        }
+    }
+
+    fn validate_enum(
+        &self,
+        value: &Value,
+        allowed: Option<&[Value]>,
+        field: Option<&str>,
+        index: Option<usize>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let allowed = match allowed {
+            Some(allowed) => allowed,
+            None => return,
+        };
+
+        let matches = allowed.iter().any(|member| {
+            member == value
+                || match (self.float_enum_epsilon, member.as_f64(), value.as_f64()) {
+                    (Some(epsilon), Some(member), Some(value)) => (member - value).abs() <= epsilon,
+                    _ => false,
+                }
+        });
+
+        if !matches {
+            errors.push(ValidationError::new(
+                ValidationError::ENUM_MISMATCH,
+                format!(
+                    "Invalid value{}. Expected one of: {}",
+                    location_label(field, index),
+                    Value::from(allowed.to_vec())
+                ),
+            ));
+        }
+    }
+
+    fn validate_type_schema(
+        &self,
+        data: &Value,
+        schema: &Value,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(type_value) = schema.get("type") {
+            if let Some(expected_type) = type_value.as_str() {
+                if !self.validate_type(data, expected_type) {
+                    errors.push(ValidationError::new(
+                        ValidationError::TYPE_MISMATCH,
+                        format!(
+                            "Invalid type. Expected: {}{}",
+                            expected_type,
+                            description_suffix(schema_description(schema))
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn validate_property_type(
+        &self,
+        data: &Value,
+        property_name: &str,
+        property_schema: &Value,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(property_type) = property_schema.get("type") {
+            if let Some(expected_type) = property_type.as_str() {
+                if let Some(property_value) = self.get_field(data, property_name) {
+                    if !self.validate_type(property_value, expected_type) {
+                        errors.push(ValidationError::new(
+                            ValidationError::TYPE_MISMATCH,
+                            format!(
+                                "Invalid type for field '{}'. Expected: {}{}",
+                                property_name,
+                                expected_type,
+                                description_suffix(schema_description(property_schema))
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_properties(
+        &self,
+        data: &Value,
+        schema: &Value,
+        root: &Value,
+        depth: usize,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(properties) = schema.get("properties") {
+            if data.is_object() && properties.is_object() {
+                if let Some(properties_obj) = properties.as_object() {
+                    for (property_name, property_schema) in sorted_properties(properties_obj) {
+                        if self.should_stop(errors) {
+                            return;
+                        }
+                        if let Some(property_value) = self.get_field(data, property_name) {
+                            let start = errors.len();
+                            self.with_resolved(
+                                property_schema,
+                                root,
+                                0,
+                                errors,
+                                &mut |property_schema, root, errors| {
+                                    self.validate_access(
+                                        property_name,
+                                        schema_flag(property_schema, "readOnly"),
+                                        schema_flag(property_schema, "writeOnly"),
+                                        errors,
+                                    );
+                                    self.validate_property_type(
+                                        data,
+                                        property_name,
+                                        property_schema,
+                                        errors,
+                                    );
+                                    self.validate_enum(
+                                        property_value,
+                                        schema_enum(property_schema),
+                                        Some(property_name),
+                                        None,
+                                        errors,
+                                    );
+                                    self.validate_pattern(
+                                        property_value,
+                                        schema_pattern(property_schema),
+                                        Some(property_name),
+                                        None,
+                                        errors,
+                                    );
+                                    self.validate_string_length(
+                                        property_value,
+                                        schema_length(property_schema, "minLength"),
+                                        schema_length(property_schema, "maxLength"),
+                                        Some(property_name),
+                                        None,
+                                        errors,
+                                    );
+                                    self.validate_items(
+                                        property_value,
+                                        property_schema,
+                                        root,
+                                        Some(property_name),
+                                        depth + 1,
+                                        errors,
+                                    );
+                                    if property_value.is_object()
+                                        && !self.should_stop(errors)
+                                        && !self.depth_exceeded(depth + 1, errors)
+                                    {
+                                        self.validate_required_fields(
+                                            property_value,
+                                            property_schema,
+                                            errors,
+                                        );
+                                        self.validate_properties(
+                                            property_value,
+                                            property_schema,
+                                            root,
+                                            depth + 1,
+                                            errors,
+                                        );
+                                    }
+                                    if !self.should_stop(errors) {
+                                        self.validate_custom_rule(
+                                            property_value,
+                                            schema_custom_rule(property_schema),
+                                            errors,
+                                        );
+                                    }
+                                },
+                            );
+                            prefix_paths(&mut errors[start..], property_name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Validates array elements against `prefixItems` (positional) and `items`.
+    /// Elements beyond the `prefixItems` length are governed by `items`, if present.
+    fn validate_items(
+        &self,
+        data: &Value,
+        schema: &Value,
+        root: &Value,
+        field: Option<&str>,
+        depth: usize,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let elements = match data.as_array() {
+            Some(elements) => elements,
+            None => return,
+        };
+        if self.depth_exceeded(depth, errors) {
+            return;
+        }
+
+        let prefix_items = schema
+            .get("prefixItems")
+            .and_then(|prefix| prefix.as_array())
+            .map(|prefix| prefix.as_slice())
+            .unwrap_or(&[]);
+
+        for (index, element) in elements.iter().enumerate() {
+            if self.should_stop(errors) {
+                return;
+            }
+            let start = errors.len();
+            let item_schema = match prefix_items.get(index) {
+                Some(item_schema) => Some(item_schema),
+                None => match schema.get("items") {
+                    Some(Value::Bool(false)) => {
+                        errors.push(unexpected_item(field, index));
+                        None
+                    }
+                    Some(item_schema) if item_schema.is_object() => Some(item_schema),
+                    _ => None,
+                },
+            };
+
+            if let Some(item_schema) = item_schema {
+                self.with_resolved(
+                    item_schema,
+                    root,
+                    0,
+                    errors,
+                    &mut |item_schema, root, errors| {
+                        self.validate_item(
+                            element,
+                            item_schema,
+                            root,
+                            (field, index),
+                            depth + 1,
+                            errors,
+                        )
+                    },
+                );
+            }
+            prefix_paths(&mut errors[start..], &index.to_string());
+        }
+    }
+
+    /// Validates one array element against its item schema. The element's
+    /// position is its array's field, if any, and its index.
+    fn validate_item(
+        &self,
+        element: &Value,
+        item_schema: &Value,
+        root: &Value,
+        (field, index): (Option<&str>, usize),
+        depth: usize,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(expected_type) = item_schema.get("type").and_then(|t| t.as_str()) {
+            if !self.validate_type(element, expected_type) {
+                errors.push(item_type_mismatch(field, index, expected_type));
+                return;
+            }
+        }
+
+        self.validate_enum(
+            element,
+            schema_enum(item_schema),
+            field,
+            Some(index),
+            errors,
+        );
+        self.validate_pattern(
+            element,
+            schema_pattern(item_schema),
+            field,
+            Some(index),
+            errors,
+        );
+        self.validate_string_length(
+            element,
+            schema_length(item_schema, "minLength"),
+            schema_length(item_schema, "maxLength"),
+            field,
+            Some(index),
+            errors,
+        );
+        if !self.should_stop(errors) {
+            self.validate_required_fields(element, item_schema, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_properties(element, item_schema, root, depth, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_items(element, item_schema, root, field, depth, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_custom_rule(element, schema_custom_rule(item_schema), errors);
+        }
+    }
+
+    fn validate_compiled_required(
+        &self,
+        data: &Value,
+        compiled: &CompiledSchema,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if self.ignore_required {
+            return;
+        }
+        for field_name in compiled.required() {
+            if self.should_stop(errors) {
+                return;
+            }
+            self.check_required_field(data, field_name, errors);
+        }
+    }
+
+    fn validate_compiled_properties(
+        &self,
+        data: &Value,
+        compiled: &CompiledSchema,
+        refs: &[CompiledSchema],
+        depth: usize,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if !data.is_object() {
+            return;
+        }
+
+        for (property_name, property_schema) in &compiled.properties {
+            if self.should_stop(errors) {
+                return;
+            }
+            if let Some(property_value) = self.get_field(data, property_name) {
+                let start = errors.len();
+                let property_schema = property_schema.resolve(refs);
+                self.validate_access(
+                    property_name,
+                    property_schema.read_only,
+                    property_schema.write_only,
+                    errors,
+                );
+                if let Some(expected_type) = property_schema.schema_type() {
+                    if !self.validate_type(property_value, expected_type) {
+                        errors.push(ValidationError::new(
+                            ValidationError::TYPE_MISMATCH,
+                            format!(
+                                "Invalid type for field '{}'. Expected: {}{}",
+                                property_name,
+                                expected_type,
+                                description_suffix(property_schema.description.as_deref())
+                            ),
+                        ));
+                    }
+                }
+                self.validate_enum(
+                    property_value,
+                    property_schema.enum_values.as_deref(),
+                    Some(property_name),
+                    None,
+                    errors,
+                );
+                self.validate_compiled_pattern(
+                    property_value,
+                    property_schema.pattern.as_deref(),
+                    Some(property_name),
+                    None,
+                    errors,
+                );
+                self.validate_string_length(
+                    property_value,
+                    property_schema.min_length,
+                    property_schema.max_length,
+                    Some(property_name),
+                    None,
+                    errors,
+                );
+                self.validate_compiled_items(
+                    property_value,
+                    property_schema,
+                    refs,
+                    Some(property_name),
+                    depth + 1,
+                    errors,
+                );
+                if property_value.is_object()
+                    && !self.should_stop(errors)
+                    && !self.depth_exceeded(depth + 1, errors)
+                {
+                    self.validate_compiled_required(property_value, property_schema, errors);
+                    self.validate_compiled_properties(
+                        property_value,
+                        property_schema,
+                        refs,
+                        depth + 1,
+                        errors,
+                    );
+                }
+                if !self.should_stop(errors) {
+                    self.validate_custom_rule(
+                        property_value,
+                        property_schema.custom_rule.as_deref(),
+                        errors,
+                    );
+                }
+                prefix_paths(&mut errors[start..], property_name);
+            }
+        }
+    }
+
+    fn validate_compiled_items(
+        &self,
+        data: &Value,
+        compiled: &CompiledSchema,
+        refs: &[CompiledSchema],
+        field: Option<&str>,
+        depth: usize,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let elements = match data.as_array() {
+            Some(elements) => elements,
+            None => return,
+        };
+        if self.depth_exceeded(depth, errors) {
+            return;
+        }
+
+        for (index, element) in elements.iter().enumerate() {
+            if self.should_stop(errors) {
+                return;
+            }
+            let start = errors.len();
+            let item_schema = match compiled.prefix_items.get(index) {
+                Some(item_schema) => Some(item_schema),
+                None => match &compiled.items {
+                    CompiledItems::Forbidden => {
+                        errors.push(unexpected_item(field, index));
+                        None
+                    }
+                    CompiledItems::Schema(item_schema) => Some(item_schema.as_ref()),
+                    CompiledItems::Any => None,
+                },
+            };
+
+            if let Some(item_schema) = item_schema {
+                self.validate_compiled_item(
+                    element,
+                    item_schema.resolve(refs),
+                    refs,
+                    (field, index),
+                    depth + 1,
+                    errors,
+                );
+            }
+            prefix_paths(&mut errors[start..], &index.to_string());
+        }
+    }
+
+    /// Validates one array element against its compiled item schema, like
+    /// [`Validator::validate_item`].
+    fn validate_compiled_item(
+        &self,
+        element: &Value,
+        item_schema: &CompiledSchema,
+        refs: &[CompiledSchema],
+        (field, index): (Option<&str>, usize),
+        depth: usize,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(expected_type) = item_schema.schema_type() {
+            if !self.validate_type(element, expected_type) {
+                errors.push(item_type_mismatch(field, index, expected_type));
+                return;
+            }
+        }
+
+        self.validate_enum(
+            element,
+            item_schema.enum_values.as_deref(),
+            field,
+            Some(index),
+            errors,
+        );
+        self.validate_compiled_pattern(
+            element,
+            item_schema.pattern.as_deref(),
+            field,
+            Some(index),
+            errors,
+        );
+        self.validate_string_length(
+            element,
+            item_schema.min_length,
+            item_schema.max_length,
+            field,
+            Some(index),
+            errors,
+        );
+        if !self.should_stop(errors) {
+            self.validate_compiled_required(element, item_schema, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_compiled_properties(element, item_schema, refs, depth, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_compiled_items(element, item_schema, refs, field, depth, errors);
+        }
+        if !self.should_stop(errors) {
+            self.validate_custom_rule(element, item_schema.custom_rule.as_deref(), errors);
+        }
+    }
+}
+
+/// Validates data against a schema with the default validation options,
+/// without needing a [`SchemaLoader`].
+pub fn validate_value(data: &Value, schema: &Value) -> ValidationResult {
+    Validator::standalone().validate_data(data, schema)
+}
+
+/// Returns the structural problems with a schema, as reported by
+/// [`Validator::validate_schema`].
+pub(crate) fn schema_errors(schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_schema_node(schema, "", &mut errors);
+    errors
+}
+
+fn validate_schema_node(schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let location = if path.is_empty() { "schema root" } else { path };
+
+    let schema_obj = match schema.as_object() {
+        Some(schema_obj) => schema_obj,
+        None => {
+            errors.push(format!("Schema must be an object at {}", location));
+            return;
+        }
+    };
+
+    if let Some(type_value) = schema_obj.get("type") {
+        let type_names = match type_value {
+            Value::String(type_name) => vec![type_name.as_str()],
+            Value::Array(types) if types.iter().all(|t| t.is_string()) => {
+                types.iter().filter_map(|t| t.as_str()).collect()
+            }
+            _ => {
+                errors.push(format!(
+                    "'type' must be a string or an array of strings at {}",
+                    location
+                ));
+                Vec::new()
+            }
+        };
+
+        for type_name in type_names {
+            if !KNOWN_TYPES.contains(&type_name) {
+                errors.push(format!("Unknown type '{}' at {}", type_name, location));
+            }
+        }
+    }
+
+    if let Some(required) = schema_obj.get("required") {
+        let all_strings = required
+            .as_array()
+            .map(|fields| fields.iter().all(|field| field.is_string()))
+            .unwrap_or(false);
+        if !all_strings {
+            errors.push(format!(
+                "'required' must be an array of strings at {}",
+                location
+            ));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for field_name in required
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|f| f.as_str())
+        {
+            if !seen.insert(field_name) {
+                errors.push(format!(
+                    "Duplicate entry in required: {} at {}",
+                    field_name, location
+                ));
+            }
+        }
+    }
+
+    if let Some(enum_value) = schema_obj.get("enum") {
+        if !enum_value.is_array() {
+            errors.push(format!("'enum' must be an array at {}", location));
+        }
+    }
+
+    if let Some(pattern) = schema_obj.get("pattern") {
+        match pattern.as_str() {
+            Some(pattern) if Regex::new(pattern).is_err() => errors.push(format!(
+                "'pattern' is not a valid regular expression at {}",
+                location
+            )),
+            Some(_) => {}
+            None => errors.push(format!("'pattern' must be a string at {}", location)),
+        }
+    }
+
+    for keyword in ["minLength", "maxLength"] {
+        if let Some(length) = schema_obj.get(keyword) {
+            if !length.is_u64() {
+                errors.push(format!(
+                    "'{}' must be a non-negative integer at {}",
+                    keyword, location
+                ));
+            }
+        }
+    }
+
+    for keyword in ["description", "$ref", "x-custom"] {
+        if let Some(value) = schema_obj.get(keyword) {
+            if !value.is_string() {
+                errors.push(format!("'{}' must be a string at {}", keyword, location));
+            }
+        }
+    }
+
+    if let Some(properties) = schema_obj.get("properties") {
+        match properties.as_object() {
+            Some(properties_obj) => {
+                for (property_name, property_schema) in sorted_properties(properties_obj) {
+                    validate_schema_node(
+                        property_schema,
+                        &format!("{}/properties/{}", path, property_name),
+                        errors,
+                    );
+                }
+            }
+            None => errors.push(format!("'properties' must be an object at {}", location)),
+        }
+    }
+
+    if let Some(prefix_items) = schema_obj.get("prefixItems") {
+        match prefix_items.as_array() {
+            Some(prefix_schemas) => {
+                for (index, item_schema) in prefix_schemas.iter().enumerate() {
+                    validate_schema_node(
+                        item_schema,
+                        &format!("{}/prefixItems/{}", path, index),
+                        errors,
+                    );
+                }
+            }
+            None => errors.push(format!("'prefixItems' must be an array at {}", location)),
+        }
+    }
+
+    if let Some(items) = schema_obj.get("items") {
+        if !items.is_boolean() {
+            validate_schema_node(items, &format!("{}/items", path), errors);
+        }
+    }
+}
+
+/// Iterates the properties sorted by name, so errors come out in the same order
+/// whether or not serde_json preserves insertion order. Properties that are
+/// already in order are iterated in place without allocating.
+pub(crate) fn sorted_properties(
+    properties: &serde_json::Map<String, Value>,
+) -> impl Iterator<Item = (&String, &Value)> {
+    let in_order = properties
+        .keys()
+        .zip(properties.keys().skip(1))
+        .all(|(a, b)| a <= b);
+
+    let mut reordered: Vec<(&String, &Value)> = Vec::new();
+    if !in_order {
+        reordered = properties.iter().collect();
+        reordered.sort_by_key(|(name, _)| *name);
+    }
+
+    in_order
+        .then(|| properties.iter())
+        .into_iter()
+        .flatten()
+        .chain(reordered)
+}
+
+/// Parses a string into the JSON value of the expected scalar type, if it can.
+fn coerce_string(text: &str, expected_type: &str) -> Option<Value> {
+    match expected_type {
+        "integer" => text
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| text.parse::<u64>().map(Value::from))
+            .ok(),
+        "number" => text.parse::<serde_json::Number>().ok().map(Value::Number),
+        "boolean" => text.parse::<bool>().ok().map(Value::Bool),
+        _ => None,
+    }
+}
+
+fn header_errors(header: &Header) -> Vec<ValidationError> {
+    if header_is_missing(header) {
+        return vec![ValidationError::new(
+            ValidationError::HEADER_MISSING,
+            "Header is required".to_string(),
+        )];
+    }
+
+    [
+        (
+            &header.schema_category,
+            "Schema category is required in header",
+        ),
+        (&header.schema_name, "Schema name is required in header"),
+        (
+            &header.schema_version,
+            "Schema version is required in header",
+        ),
+    ]
+    .into_iter()
+    .filter(|(value, _)| value.is_empty())
+    .map(|(_, message)| {
+        ValidationError::new(ValidationError::HEADER_FIELD_MISSING, message.to_string())
+    })
+    .collect()
+}
+
+fn pattern_mismatch(field: Option<&str>, index: Option<usize>, pattern: &str) -> ValidationError {
+    ValidationError::new(
+        ValidationError::PATTERN_MISMATCH,
+        format!(
+            "Value{} does not match pattern: {}",
+            location_label(field, index),
+            pattern
+        ),
+    )
+}
+
+fn unexpected_item(field: Option<&str>, index: usize) -> ValidationError {
+    let message = match field {
+        Some(name) => format!("Unexpected item for field '{}' at index {}", name, index),
+        None => format!("Unexpected item at index {}", index),
+    };
+    ValidationError::new(ValidationError::UNEXPECTED_ITEM, message)
+}
+
+fn item_type_mismatch(field: Option<&str>, index: usize, expected_type: &str) -> ValidationError {
+    let message = match field {
+        Some(name) => format!(
+            "Invalid type for field '{}' at index {}. Expected: {}",
+            name, index, expected_type
+        ),
+        None => format!(
+            "Invalid type at index {}. Expected: {}",
+            index, expected_type
+        ),
+    };
+    ValidationError::new(ValidationError::TYPE_MISMATCH, message)
+}
+
+fn header_is_missing(header: &Header) -> bool {
+    header.schema_category.is_empty()
+        && header.schema_name.is_empty()
+        && header.schema_version.is_empty()
+}
+
+/// Converts a whole-valued float to an integer `Value`, if it fits in `i64` or
+/// `u64` without saturating.
+fn integer_value(number: f64) -> Option<Value> {
+    if number.fract() != 0.0 {
+        None
+    } else if number >= i64::MIN as f64 && number < i64::MAX as f64 {
+        Some(Value::from(number as i64))
+    } else if number >= 0.0 && number < u64::MAX as f64 {
+        Some(Value::from(number as u64))
+    } else {
+        None
+    }
+}
+
+fn schema_description(schema: &Value) -> Option<&str> {
+    schema.get("description").and_then(|d| d.as_str())
+}
+
+/// Formats a schema description as a trailing parenthetical for error messages.
+fn description_suffix(description: Option<&str>) -> String {
+    match description {
+        Some(description) => format!(" ({})", description),
+        None => String::new(),
+    }
+}
+
+/// Records the error count on the current tracing span and emits a debug event
+/// when validation failed. Does nothing without the `tracing` feature.
+fn record_outcome(result: ValidationResult) -> ValidationResult {
+    #[cfg(feature = "tracing")]
+    {
+        tracing::Span::current().record("error_count", result.errors.len());
+        if !result.valid {
+            tracing::debug!(errors = %result.error_message(), "Validation failed");
+        }
+    }
+    result
+}
+
+fn schema_flag(schema: &Value, keyword: &str) -> bool {
+    schema.get(keyword).and_then(|f| f.as_bool()) == Some(true)
+}
+
+fn schema_custom_rule(schema: &Value) -> Option<&str> {
+    schema.get("x-custom").and_then(|r| r.as_str())
+}
+
+fn schema_length(schema: &Value, keyword: &str) -> Option<u64> {
+    schema.get(keyword).and_then(|n| n.as_u64())
+}
+
+fn schema_pattern(schema: &Value) -> Option<&str> {
+    schema.get("pattern").and_then(|p| p.as_str())
+}
+
+fn schema_enum(schema: &Value) -> Option<&[Value]> {
+    schema
+        .get("enum")
+        .and_then(|e| e.as_array())
+        .map(|e| e.as_slice())
+}
+
+/// Describes where a value sits for error messages, e.g. ` for field 'tags' at index 2`.
+pub(crate) fn location_label(field: Option<&str>, index: Option<usize>) -> String {
+    match (field, index) {
+        (Some(name), Some(index)) => format!(" for field '{}' at index {}", name, index),
+        (Some(name), None) => format!(" for field '{}'", name),
+        (None, Some(index)) => format!(" at index {}", index),
+        (None, None) => String::new(),
+    }
+}
+
+/// Returns the JSON pointer to an object's field.
+fn pointer_to(field_name: &str) -> String {
+    format!("/{}", compatibility::escape_pointer_token(field_name))
+}
+
+/// Prepends the field or index `token` to the paths of errors found inside it.
+fn prefix_paths(errors: &mut [ValidationError], token: &str) {
+    for error in errors {
+        error.path = format!("{}{}", pointer_to(token), error.path);
+    }
+}
+
+/// Removes the field and index an error message names, which its path already
+/// gives, e.g. `Invalid type for field 'age'. Expected: integer` becomes
+/// `Invalid type. Expected: integer`.
+fn bare_message(message: &str) -> String {
+    for prefix in [
+        "Required field missing: ",
+        "Required field is null: ",
+        "Read-only field present: ",
+        "Write-only field present: ",
+    ] {
+        if message.starts_with(prefix) {
+            return prefix.trim_end_matches(": ").to_string();
+        }
+    }
+
+    let mut bare = message.to_string();
+    if let Some((before, rest)) = bare.split_once(" for field '") {
+        if let Some((_, after)) = rest.split_once('\'') {
+            bare = format!("{}{}", before, after);
+        }
+    }
+    if let Some((before, rest)) = bare.split_once(" at index ") {
+        let after = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+        bare = format!("{}{}", before, after);
+    }
+    bare
+}
+
+/// Formats a duration compactly, rounded to the second, e.g. `3h`, `1d2h` or `5m30s`.
+fn format_duration(duration: Duration) -> String {
+    let mut seconds = (duration.num_milliseconds() + 500) / 1000;
+    let mut formatted = String::new();
+
+    for (unit, unit_seconds) in [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)] {
+        if seconds >= unit_seconds {
+            formatted.push_str(&format!("{}{}", seconds / unit_seconds, unit));
+            seconds %= unit_seconds;
+        }
+    }
+
+    if formatted.is_empty() {
+        "0s".to_string()
+    } else {
+        formatted
     }
 }
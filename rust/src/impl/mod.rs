@@ -1,3 +1,5 @@
+pub mod observer;
 pub mod service;
 
-pub use service::PactsService;
+pub use observer::ValidationObserver;
+pub use service::{MissingSchemaPolicy, PactsService};
@@ -0,0 +1,10 @@
+use crate::ValidationResult;
+use std::time::Duration;
+
+/// Hook for observing validations performed by [`crate::PactsService`], e.g. to
+/// record pass/fail counts and per-schema latency.
+pub trait ValidationObserver: Send + Sync {
+    /// Called after each validation with the schema reference, its result, and
+    /// how long it took. Does nothing by default.
+    fn on_validate(&self, _schema: &str, _result: &ValidationResult, _elapsed: Duration) {}
+}
@@ -1,26 +1,102 @@
-use crate::{Envelope, Header, SchemaLoader, ValidationResult, Validator};
+use crate::core::digest::sort_keys;
+use crate::core::error::{FileValidationError, PactsError, SchemaLoadError};
+#[cfg(feature = "remote")]
+use crate::core::remote::fetch_schema_document;
+use crate::core::schema_loader::parse_version_number;
+use crate::r#impl::ValidationObserver;
+use crate::{
+    schema_digest, Envelope, Header, SchemaCatalog, SchemaCatalogEntry, SchemaLoader,
+    ValidationError, ValidationResult, Validator,
+};
+use log::warn;
+use serde::Serialize;
 use serde_json::Value;
-use std::cell::RefCell;
-use std::sync::Arc;
+#[cfg(feature = "remote")]
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How `PactsService::validate` treats envelopes whose schema cannot be loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingSchemaPolicy {
+    /// Report the envelope as invalid
+    #[default]
+    Fail,
+    /// Report the envelope as valid with a warning
+    Warn,
+    /// Report the envelope as valid without validating it
+    Skip,
+}
 
 /// Service struct for convenient Pacts operations
 pub struct PactsService {
     validator: Arc<Validator>,
-    schema_loader: Arc<RefCell<SchemaLoader>>,
+    schema_loader: Arc<Mutex<SchemaLoader>>,
+    observer: Option<Box<dyn ValidationObserver>>,
+    metadata_schema: Option<Value>,
+    on_missing_schema: MissingSchemaPolicy,
+    #[cfg(feature = "remote")]
+    allowed_schema_hosts: Vec<String>,
+    #[cfg(feature = "remote")]
+    url_schema_cache: Mutex<HashMap<String, Arc<Value>>>,
 }
 
 impl PactsService {
     /// Creates a new PactsService
     pub fn new(schema_root: String, domain: String, version: String) -> Self {
-        let schema_loader = SchemaLoader::new(schema_root, domain, version);
-        let validator = Validator::new(schema_loader.clone());
+        let schema_loader = Arc::new(Mutex::new(SchemaLoader::new(schema_root, domain, version)));
+        let validator = Validator::with_shared_loader(Arc::clone(&schema_loader));
 
         Self {
             validator: Arc::new(validator),
-            schema_loader: Arc::new(RefCell::new(schema_loader)),
+            schema_loader,
+            observer: None,
+            metadata_schema: None,
+            on_missing_schema: MissingSchemaPolicy::default(),
+            #[cfg(feature = "remote")]
+            allowed_schema_hosts: Vec::new(),
+            #[cfg(feature = "remote")]
+            url_schema_cache: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Sets the observer notified after each `validate`/`validate_data` call
+    pub fn set_observer(&mut self, observer: Box<dyn ValidationObserver>) {
+        self.observer = Some(observer);
+    }
+
+    /// Sets how `validate` treats envelopes whose schema cannot be loaded
+    pub fn set_missing_schema_policy(&mut self, policy: MissingSchemaPolicy) {
+        self.on_missing_schema = policy;
+    }
+
+    /// Gets how `validate` treats envelopes whose schema cannot be loaded
+    pub fn missing_schema_policy(&self) -> MissingSchemaPolicy {
+        self.on_missing_schema
+    }
+
+    /// Validates envelopes with the given content type against the schema
+    /// `category`/`name` instead of the one their header names. See
+    /// [`Validator::register_content_type_schema`]
+    pub fn register_content_type_schema(
+        &mut self,
+        content_type: String,
+        category: String,
+        name: String,
+    ) {
+        Arc::make_mut(&mut self.validator).register_content_type_schema(
+            content_type,
+            category,
+            name,
+        );
+    }
+
+    /// Sets the schema `validate_metadata` checks envelope metadata against
+    pub fn set_metadata_schema(&mut self, schema: Value) {
+        self.metadata_schema = Some(schema);
+    }
+
     /// Creates an envelope
     pub fn create_envelope(
         &self,
@@ -29,7 +105,7 @@ impl PactsService {
         data: Value,
     ) -> Envelope {
         let header = Header::with_content_type(
-            self.schema_loader.borrow().get_version().to_string(),
+            self.loader().get_version().to_string(),
             schema_category,
             schema_name,
             "application/json".to_string(),
@@ -37,32 +113,637 @@ impl PactsService {
         Envelope::new(header, data)
     }
 
+    /// Creates an envelope whose data is `partial_data` with each omitted
+    /// property set to its schema `default`, or the loader's error if the schema
+    /// cannot be loaded
+    pub fn create_envelope_with_defaults(
+        &self,
+        schema_category: String,
+        schema_name: String,
+        partial_data: Value,
+    ) -> Result<Envelope, SchemaLoadError> {
+        let version = self.loader().get_version().to_string();
+        let schema = self
+            .loader()
+            .load_schema_version(&version, &schema_category, &schema_name)?;
+
+        let data = self.validator.normalize(&partial_data, &schema);
+        Ok(self.create_envelope(schema_category, schema_name, data))
+    }
+
     /// Validates an envelope
     pub fn validate(&self, envelope: &Envelope) -> ValidationResult {
-        // We need to clone the validator to get a mutable reference
-        let mut validator = (*self.validator).clone();
-        validator.validate(envelope)
+        let started = Instant::now();
+        let schema = self.validator.load_envelope_schema(envelope);
+        let result = match self.check_missing_schema(envelope, schema.as_ref()) {
+            Some(result) => result,
+            None => {
+                let result = self
+                    .validator
+                    .validate_with_schema(envelope, schema.as_ref());
+                self.annotate_result(envelope, schema.as_ref(), result)
+            }
+        };
+        self.notify_observer(&result, started);
+        result
     }
 
-    /// Validates data against a specific schema
+    /// Validates an envelope and, if it is valid, returns its canonical JSON: compact,
+    /// with the keys of every object sorted, so equal envelopes serialize to
+    /// identical bytes
+    pub fn validate_and_canonicalize(&self, envelope: &Envelope) -> Result<String, PactsError> {
+        let result = self.validate(envelope);
+        if !result.is_valid() {
+            return Err(PactsError::Validation(Box::new(result)));
+        }
+
+        let canonical = sort_keys(serde_json::to_value(envelope)?);
+        Ok(serde_json::to_string(&canonical)?)
+    }
+
+    /// Validates envelopes across the rayon thread pool, returning the results
+    /// in input order. Workers share the service's validator and its schema
+    /// cache; the missing schema policy and result annotation run on the
+    /// calling thread
+    #[cfg(feature = "parallel")]
+    pub fn validate_batch_parallel(&self, envelopes: &[Envelope]) -> Vec<ValidationResult> {
+        use rayon::prelude::*;
+
+        let loaded: Vec<_> = envelopes
+            .iter()
+            .map(|envelope| {
+                let started = Instant::now();
+                let schema = self.validator.load_envelope_schema(envelope);
+                let skipped = self
+                    .check_missing_schema(envelope, schema.as_ref())
+                    .map(|result| (result, started));
+                (schema, skipped)
+            })
+            .collect();
+
+        let validator: &Validator = &self.validator;
+        let validated: Vec<Option<(ValidationResult, Duration)>> = envelopes
+            .par_iter()
+            .zip(loaded.par_iter())
+            .map(|(envelope, (schema, skipped))| match skipped {
+                Some(_) => None,
+                None => {
+                    let started = Instant::now();
+                    let result = validator.validate_with_schema(envelope, schema.as_ref());
+                    Some((result, started.elapsed()))
+                }
+            })
+            .collect();
+
+        envelopes
+            .iter()
+            .zip(loaded)
+            .zip(validated)
+            .map(|((envelope, (schema, skipped)), validated)| {
+                let (result, elapsed) = match (skipped, validated) {
+                    (Some((result, started)), _) => (result, started.elapsed()),
+                    (None, Some((result, elapsed))) => {
+                        let started = Instant::now();
+                        let result = self.annotate_result(envelope, schema.as_ref(), result);
+                        (result, elapsed + started.elapsed())
+                    }
+                    (None, None) => unreachable!("every envelope is skipped or validated"),
+                };
+                self.notify_observer_elapsed(&result, elapsed);
+                result
+            })
+            .collect()
+    }
+
+    /// Returns the result for an envelope whose schema could not be loaded when the
+    /// missing schema policy lets it through without validation. Under
+    /// [`MissingSchemaPolicy::Warn`] the result carries a warning
+    fn check_missing_schema(
+        &self,
+        envelope: &Envelope,
+        schema: Option<&Result<Arc<Value>, ValidationError>>,
+    ) -> Option<ValidationResult> {
+        if self.on_missing_schema == MissingSchemaPolicy::Fail {
+            return None;
+        }
+
+        let schema_ref = self.envelope_schema_ref(envelope)?;
+        let error = match schema {
+            Some(Err(error)) => error,
+            _ => return None,
+        };
+
+        let result = ValidationResult::success().with_schema_ref(schema_ref);
+        if self.on_missing_schema == MissingSchemaPolicy::Warn {
+            let warning = format!("Passing envelope without validation: {}", error);
+            warn!("{}", warning);
+            return Some(result.with_warning(warning));
+        }
+        Some(result)
+    }
+
+    /// Adds the schema reference, and a warning if the schema is deprecated, to
+    /// the validator's result for an envelope
+    fn annotate_result(
+        &self,
+        envelope: &Envelope,
+        schema: Option<&Result<Arc<Value>, ValidationError>>,
+        result: ValidationResult,
+    ) -> ValidationResult {
+        match self.envelope_schema_ref(envelope) {
+            Some(schema_ref) => {
+                let deprecated = matches!(
+                    schema,
+                    Some(Ok(schema)) if schema.get("deprecated") == Some(&Value::Bool(true))
+                );
+
+                let result = if deprecated {
+                    warn!("Validated against deprecated schema {}", schema_ref);
+                    result.with_warning(format!("Schema {} is deprecated", schema_ref))
+                } else {
+                    result
+                };
+                result.with_schema_ref(schema_ref)
+            }
+            None => result,
+        }
+    }
+
+    /// Builds an envelope from a raw body in the header's content type and
+    /// validates it
+    pub fn validate_body(&self, header: Header, body: &[u8]) -> ValidationResult {
+        match Envelope::from_body(header, body) {
+            Ok(envelope) => self.validate(&envelope),
+            Err(e) => ValidationResult::failure(vec![e]),
+        }
+    }
+
+    /// Validates envelope metadata against the configured metadata schema, treating
+    /// absent metadata as an empty object. Passes if no metadata schema is set
+    pub fn validate_metadata(&self, envelope: &Envelope) -> ValidationResult {
+        let schema = match &self.metadata_schema {
+            Some(schema) => schema,
+            None => return ValidationResult::success(),
+        };
+
+        let metadata = match envelope.metadata() {
+            Some(metadata) => Value::Object(
+                metadata
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+            ),
+            None => Value::Object(serde_json::Map::new()),
+        };
+
+        self.validator.validate_data(&metadata, schema)
+    }
+
+    /// Validates every envelope in a document holding a single envelope or an array
+    /// of envelopes. An element that is not an envelope yields a failed result at its
+    /// position, and a document that is not JSON yields a single failed result
+    pub fn validate_many(&self, s: &str) -> Vec<ValidationResult> {
+        let items = match serde_json::from_str::<Value>(s) {
+            Ok(Value::Array(items)) => items,
+            Ok(single) => vec![single],
+            Err(e) => {
+                return vec![ValidationResult::failure(vec![format!(
+                    "Invalid JSON: {}",
+                    e
+                )])]
+            }
+        };
+
+        items
+            .into_iter()
+            .enumerate()
+            .map(
+                |(index, item)| match serde_json::from_value::<Envelope>(item) {
+                    Ok(envelope) => self.validate(&envelope),
+                    Err(e) => ValidationResult::failure(vec![format!(
+                        "Invalid envelope at index {}: {}",
+                        index, e
+                    )]),
+                },
+            )
+            .collect()
+    }
+
+    /// Validates an envelope and, when its data is itself an envelope, the inner
+    /// envelopes up to `depth` levels, prefixing inner errors with `data.` per level
+    pub fn validate_nested(&self, envelope: &Envelope, depth: usize) -> ValidationResult {
+        let mut result = self.validate(envelope);
+        if depth == 0 {
+            return result;
+        }
+
+        let inner = match serde_json::from_value::<Envelope>(envelope.data().clone()) {
+            Ok(inner) => inner,
+            Err(_) => return result,
+        };
+
+        let inner_result = self.validate_nested(&inner, depth - 1);
+        if !inner_result.is_valid() {
+            for error in inner_result.get_error_details() {
+                result = result.with_error(ValidationError::new(
+                    error.code,
+                    format!("data.{}", error.message),
+                ));
+            }
+        }
+        result
+    }
+
+    /// Validates an envelope against the schema for the version in its header,
+    /// after checking that version (e.g. `v3`) is within `min..=max`
+    pub fn validate_in_range(&self, envelope: &Envelope, min: u32, max: u32) -> ValidationResult {
+        let declared = envelope.header.schema_version();
+        match parse_version_number(declared) {
+            Some(version) if (min..=max).contains(&version) => self.validate(envelope),
+            Some(version) => ValidationResult::failure(vec![format!(
+                "Version {} outside accepted range {}..={}",
+                version, min, max
+            )]),
+            None => {
+                ValidationResult::failure(vec![format!("Invalid schema version: {}", declared)])
+            }
+        }
+    }
+
+    /// Validates an envelope against the highest available schema version not
+    /// newer than the one requested in its header
+    pub fn validate_negotiated(&self, envelope: &Envelope) -> ValidationResult {
+        let requested = envelope.header.schema_version();
+        let negotiated = match self.loader().resolve_version(requested) {
+            Some(version) => version,
+            None => {
+                return ValidationResult::failure(vec![format!(
+                    "No compatible schema version available for: {}",
+                    requested
+                )])
+            }
+        };
+
+        let schema = match self.loader().load_schema_version(
+            &negotiated,
+            envelope.header.schema_category(),
+            envelope.header.schema_name(),
+        ) {
+            Ok(schema) => schema,
+            Err(e) => return ValidationResult::failure(vec![e.to_string()]),
+        };
+
+        let schema_ref = self.schema_ref(
+            &negotiated,
+            envelope.header.schema_category(),
+            envelope.header.schema_name(),
+        );
+        let mut result = self
+            .validator
+            .validate_data(envelope.data(), &schema)
+            .with_schema_ref(schema_ref);
+        result.schema_version = Some(negotiated);
+        result
+    }
+
+    /// Validates envelope data against the schema named by `metadata[key]`
+    /// within the given category
+    pub fn validate_by_metadata_schema(
+        &self,
+        envelope: &Envelope,
+        category: &str,
+        key: &str,
+    ) -> ValidationResult {
+        let schema_name = match envelope.metadata().and_then(|metadata| metadata.get(key)) {
+            Some(Value::String(schema_name)) => schema_name.as_str(),
+            Some(_) => {
+                return ValidationResult::failure(vec![format!(
+                    "Metadata key '{}' must be a string schema name",
+                    key
+                )])
+            }
+            None => {
+                return ValidationResult::failure(vec![format!(
+                    "Metadata key '{}' is required to select a schema",
+                    key
+                )])
+            }
+        };
+
+        let version = self.loader().get_version().to_string();
+        let schema = match self
+            .loader()
+            .load_schema_version(&version, category, schema_name)
+        {
+            Ok(schema) => schema,
+            Err(e) => return ValidationResult::failure(vec![e.to_string()]),
+        };
+
+        self.validator
+            .validate_data(envelope.data(), &schema)
+            .with_schema_ref(self.schema_ref(&version, category, schema_name))
+    }
+
+    /// Sets the hosts `validate_by_url` may fetch schemas from; all others are
+    /// rejected. No hosts are allowed by default
+    #[cfg(feature = "remote")]
+    pub fn set_allowed_schema_hosts(&mut self, hosts: Vec<String>) {
+        self.allowed_schema_hosts = hosts;
+    }
+
+    /// Validates envelope data against the schema fetched from the URL in
+    /// `metadata[url_key]`, caching fetched schemas by URL
+    #[cfg(feature = "remote")]
+    pub fn validate_by_url(&self, envelope: &Envelope, url_key: &str) -> ValidationResult {
+        let url = match envelope
+            .metadata()
+            .and_then(|metadata| metadata.get(url_key))
+        {
+            Some(Value::String(url)) => url.as_str(),
+            Some(_) => {
+                return ValidationResult::failure(vec![format!(
+                    "Metadata key '{}' must be a string schema URL",
+                    url_key
+                )])
+            }
+            None => {
+                return ValidationResult::failure(vec![format!(
+                    "Metadata key '{}' is required to locate a schema",
+                    url_key
+                )])
+            }
+        };
+
+        let cached = self
+            .url_schema_cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(url)
+            .cloned();
+        let schema = match cached {
+            Some(schema) => schema,
+            None => match fetch_schema_document(url, &self.allowed_schema_hosts) {
+                Ok(schema) => {
+                    let schema = Arc::new(schema);
+                    self.url_schema_cache
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .insert(url.to_string(), Arc::clone(&schema));
+                    schema
+                }
+                Err(e) => return ValidationResult::failure(vec![e]),
+            },
+        };
+
+        self.validator
+            .validate_data(envelope.data(), &schema)
+            .with_schema_ref(url.to_string())
+            .with_schema_source("url".to_string())
+    }
+
+    /// Validates data against a specific schema. A schema that cannot be loaded
+    /// is reported as a validation failure
     pub fn validate_data(
         &self,
         data: &Value,
         category: &str,
         schema_name: &str,
     ) -> ValidationResult {
-        match self
-            .schema_loader
-            .borrow_mut()
-            .load_schema(category, schema_name)
-        {
-            schema => {
-                let validator = (*self.validator).clone();
-                validator.validate_data(data, &schema)
+        let started = Instant::now();
+        let version = self.loader().get_version().to_string();
+        let loaded = self
+            .loader()
+            .load_schema_version(&version, category, schema_name);
+
+        let result = match loaded {
+            Ok(schema) => self.validator.validate_data(data, &schema),
+            Err(e) => ValidationResult::from_errors(vec![ValidationError::new(
+                ValidationError::SCHEMA_UNAVAILABLE,
+                e.to_string(),
+            )]),
+        }
+        .with_schema_ref(self.schema_ref(&version, category, schema_name));
+        self.notify_observer(&result, started);
+        result
+    }
+
+    /// Validates data against the JSON schema file at `schema_path`, outside the
+    /// domain/version/category tree. A schema file that cannot be read or parsed
+    /// is reported as a validation failure
+    pub fn validate_data_with_schema_file(
+        &self,
+        data: &Value,
+        schema_path: &Path,
+    ) -> ValidationResult {
+        let started = Instant::now();
+        let result = match read_schema_file(schema_path) {
+            Ok(schema) => self.validator.validate_data(data, &schema),
+            Err(e) => ValidationResult::failure(vec![e.to_string()]),
+        }
+        .with_schema_source("file".to_string());
+        self.notify_observer(&result, started);
+        result
+    }
+
+    /// Validates data against each `(category, name)` candidate in order and returns
+    /// the first that passes along with its result. If none pass, returns `None`
+    /// and a failure listing every candidate's errors prefixed with its identity
+    pub fn validate_against_any(
+        &self,
+        data: &Value,
+        candidates: &[(String, String)],
+    ) -> (Option<(String, String)>, ValidationResult) {
+        let version = self.loader().get_version().to_string();
+        let mut errors = Vec::new();
+
+        for (category, name) in candidates {
+            let loaded = self.loader().load_schema_version(&version, category, name);
+            let schema = match loaded {
+                Ok(schema) => schema,
+                Err(e) => {
+                    errors.push(format!("{}/{}: {}", category, name, e));
+                    continue;
+                }
+            };
+
+            let result = self
+                .validator
+                .validate_data(data, &schema)
+                .with_schema_ref(self.schema_ref(&version, category, name));
+            if result.is_valid() {
+                return (Some((category.clone(), name.clone())), result);
+            }
+            errors.extend(
+                result
+                    .get_errors()
+                    .iter()
+                    .map(|e| format!("{}/{}: {}", category, name, e)),
+            );
+        }
+
+        if candidates.is_empty() {
+            errors.push("No candidate schemas given".to_string());
+        }
+        (None, ValidationResult::failure(errors))
+    }
+
+    /// Validates the JSON data file against the JSON schema file, without a
+    /// schema loader
+    pub fn validate_files(
+        schema_path: &Path,
+        data_path: &Path,
+    ) -> Result<ValidationResult, FileValidationError> {
+        let schema = read_schema_file(schema_path)?;
+
+        let data_text = std::fs::read_to_string(data_path).map_err(|source| {
+            FileValidationError::DataUnreadable {
+                path: data_path.to_path_buf(),
+                source,
+            }
+        })?;
+        let data: Value = serde_json::from_str(&data_text).map_err(|source| {
+            FileValidationError::DataInvalid {
+                path: data_path.to_path_buf(),
+                source,
             }
+        })?;
+
+        Ok(Validator::standalone().validate_data(&data, &schema))
+    }
+
+    /// Validates a batch of envelopes, flagging any envelope whose timestamp is
+    /// earlier than its predecessor's
+    pub fn validate_ordered_batch(&self, envelopes: &[Envelope]) -> Vec<ValidationResult> {
+        let mut previous: Option<&Envelope> = None;
+
+        envelopes
+            .iter()
+            .map(|envelope| {
+                let result = self.validate(envelope);
+                let out_of_order = previous
+                    .map(|prev| envelope.header.timestamp() < prev.header.timestamp())
+                    .unwrap_or(false);
+                previous = Some(envelope);
+
+                if out_of_order {
+                    result.with_error(ValidationError::new(
+                        ValidationError::OUT_OF_ORDER,
+                        "Envelope out of order".to_string(),
+                    ))
+                } else {
+                    result
+                }
+            })
+            .collect()
+    }
+
+    /// Validates raw JSON bytes against a specific schema without building an envelope
+    pub fn validate_bytes(
+        &self,
+        bytes: &[u8],
+        category: &str,
+        schema_name: &str,
+    ) -> ValidationResult {
+        match serde_json::from_slice::<Value>(bytes) {
+            Ok(data) => self.validate_data(&data, category, schema_name),
+            Err(e) => ValidationResult::from_errors(vec![ValidationError::new(
+                ValidationError::INVALID_JSON,
+                format!("Invalid JSON: {}", e),
+            )]),
         }
     }
 
+    /// Serializes `value` to JSON and validates it against a specific schema. A
+    /// value that cannot be serialized is reported as a validation failure
+    pub fn validate_value_of<T: Serialize>(
+        &self,
+        value: &T,
+        category: &str,
+        schema_name: &str,
+    ) -> ValidationResult {
+        match serde_json::to_value(value) {
+            Ok(data) => self.validate_data(&data, category, schema_name),
+            Err(e) => ValidationResult::from_errors(vec![ValidationError::new(
+                ValidationError::SERIALIZATION_FAILED,
+                format!("Failed to serialize value: {}", e),
+            )]),
+        }
+    }
+
+    /// Verifies every schema of the configured domain and version is well-formed
+    /// and that any `examples` it declares validate against it
+    pub fn verify_all_schemas(&self) -> Vec<(String, ValidationResult)> {
+        let version = self.loader().get_version().to_string();
+        let schemas = self.loader().list_schemas();
+
+        schemas
+            .into_iter()
+            .map(|(category, name)| {
+                let schema_ref = self.schema_ref(&version, &category, &name);
+                let schema = match self
+                    .loader()
+                    .load_schema_version(&version, &category, &name)
+                {
+                    Ok(schema) => schema,
+                    Err(e) => return (schema_ref, ValidationResult::failure(vec![e.to_string()])),
+                };
+
+                let mut errors = self
+                    .validator
+                    .validate_schema(&schema)
+                    .get_errors()
+                    .to_vec();
+
+                if errors.is_empty() {
+                    let examples = schema.get("examples").and_then(|e| e.as_array());
+                    for (index, example) in examples.into_iter().flatten().enumerate() {
+                        let result = self.validator.validate_data(example, &schema);
+                        errors.extend(
+                            result
+                                .get_errors()
+                                .iter()
+                                .map(|e| format!("Example {}: {}", index, e)),
+                        );
+                    }
+                }
+
+                let result = ValidationResult::new(errors.is_empty(), errors)
+                    .with_schema_ref(schema_ref.clone());
+                (schema_ref, result)
+            })
+            .collect()
+    }
+
+    /// Lists every schema available for the configured domain, across all
+    /// versions, with its digest and title
+    pub fn export_catalog(&self) -> SchemaCatalog {
+        let domain = self.loader().get_domain().to_string();
+        let schemas = self.loader().list_all_schemas();
+
+        let entries = schemas
+            .into_iter()
+            .filter_map(|(version, category, name)| {
+                let schema = self
+                    .loader()
+                    .load_schema_version(&version, &category, &name)
+                    .ok()?;
+
+                Some(SchemaCatalogEntry {
+                    digest: schema_digest(&schema),
+                    title: schema
+                        .get("title")
+                        .and_then(|t| t.as_str())
+                        .map(|t| t.to_string()),
+                    category,
+                    name,
+                    version,
+                })
+            })
+            .collect();
+
+        SchemaCatalog { domain, entries }
+    }
+
     /// Sends validated data using a provided sender function
     pub fn send_validated_data<T, F>(
         &self,
@@ -84,15 +765,92 @@ impl PactsService {
         }
     }
 
+    /// Fills schema defaults into `data` (and coerces integral floats if lenient
+    /// integers are enabled), validates it, and passes the normalized envelope
+    /// to the sender
+    pub fn send_normalized_data<T, F>(
+        &self,
+        schema_category: String,
+        schema_name: String,
+        data: Value,
+        sender: F,
+    ) -> Result<T, String>
+    where
+        F: FnOnce(&Envelope) -> Result<T, String>,
+    {
+        let version = self.loader().get_version().to_string();
+        let schema = self
+            .loader()
+            .load_schema_version(&version, &schema_category, &schema_name)
+            .map_err(|e| format!("Validation failed: {}", e))?;
+
+        let data = self.validator.normalize(&data, &schema);
+        let envelope = self.create_envelope(schema_category, schema_name, data);
+        let result = self.validator.validate_data(envelope.data(), &schema);
+
+        if result.is_valid() {
+            sender(&envelope)
+        } else {
+            Err(format!("Validation failed: {}", result.error_message()))
+        }
+    }
+
+    fn notify_observer(&self, result: &ValidationResult, started: Instant) {
+        self.notify_observer_elapsed(result, started.elapsed());
+    }
+
+    fn notify_observer_elapsed(&self, result: &ValidationResult, elapsed: Duration) {
+        if let Some(observer) = &self.observer {
+            let schema = result.get_schema_ref().unwrap_or_default();
+            observer.on_validate(schema, result, elapsed);
+        }
+    }
+
+    /// Returns the reference of the schema an envelope is validated against, as
+    /// chosen by [`Validator::resolve_schema`]
+    fn envelope_schema_ref(&self, envelope: &Envelope) -> Option<String> {
+        let version = envelope.header.schema_version();
+        let (category, name) = self
+            .validator
+            .resolve_schema(&envelope.header)
+            .filter(|_| !version.is_empty())?;
+        Some(self.schema_ref(version, category, name))
+    }
+
+    fn schema_ref(&self, version: &str, category: &str, name: &str) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            self.loader().get_domain(),
+            version,
+            category,
+            name
+        )
+    }
+
+    /// Registers an inline schema with the schema loader shared by the service and
+    /// its validator, so envelopes can be validated against it without touching
+    /// the filesystem
+    pub fn register_schema(&self, category: &str, name: &str, schema: Value) {
+        self.loader().register_schema(category, name, schema);
+    }
+
     /// Gets a reference to the validator
     pub fn validator(&self) -> &Arc<Validator> {
         &self.validator
     }
 
-    /// Gets a reference to the schema loader
-    pub fn schema_loader(&self) -> &Arc<RefCell<SchemaLoader>> {
+    /// Gets a reference to the schema loader, which is shared with the validator
+    pub fn schema_loader(&self) -> &Arc<Mutex<SchemaLoader>> {
         &self.schema_loader
     }
+
+    /// Locks the schema loader. A panic while it was locked, e.g. in
+    /// `SchemaLoader::load_schema`, leaves the cache intact, so poisoning is ignored
+    fn loader(&self) -> MutexGuard<'_, SchemaLoader> {
+        self.schema_loader
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
 }
 
 impl Default for PactsService {
@@ -100,3 +858,18 @@ impl Default for PactsService {
         Self::new("schemas".to_string(), "bees".to_string(), "v1".to_string())
     }
 }
+
+/// Returns the envelope's `(version, category, name)`, or `None` if the header
+/// leaves any of them empty
+fn read_schema_file(schema_path: &Path) -> Result<Value, FileValidationError> {
+    let schema_text = std::fs::read_to_string(schema_path).map_err(|source| {
+        FileValidationError::SchemaUnreadable {
+            path: schema_path.to_path_buf(),
+            source,
+        }
+    })?;
+    serde_json::from_str(&schema_text).map_err(|source| FileValidationError::SchemaInvalid {
+        path: schema_path.to_path_buf(),
+        source,
+    })
+}
@@ -2,11 +2,34 @@ pub mod core;
 pub mod r#impl;
 pub mod model;
 
-pub use crate::r#impl::PactsService;
+pub use crate::r#impl::ValidationObserver;
+pub use crate::r#impl::{MissingSchemaPolicy, PactsService};
+pub use core::compatibility::Incompatibility;
+pub use core::compiled_schema::CompiledSchema;
+pub use core::digest::schema_digest;
+pub use core::error::{FileValidationError, PactsError, SchemaLoadError};
+pub use core::multi_domain_loader::MultiDomainLoader;
 pub use core::schema_loader::SchemaLoader;
-pub use core::validator::{ValidationResult, Validator};
-pub use model::Envelope;
-pub use model::Header;
+pub use core::schema_source::SchemaSource;
+pub use core::validator::{
+    validate_value, CustomRule, ValidationContext, ValidationError, ValidationResult, Validator,
+};
+pub use model::{Envelope, FieldChange, ReadEnvelopeError, SchemaCatalog, SchemaCatalogEntry};
+pub use model::{Header, ParseHeaderError, TypedEnvelope};
+
+/// Version of the schema bundle compiled into this build, taken from the `SCHEMA_VERSION`
+/// environment variable at build time or the release tag in the embedded sources file.
+/// Falls back to `unknown` when neither is available.
+pub const SCHEMA_VERSION: &str = match option_env!("SCHEMA_VERSION") {
+    Some(version) => version,
+    None => "unknown",
+};
+
+/// Returns the version of the schema bundle compiled into this build.
+/// See [`SCHEMA_VERSION`].
+pub fn schema_version() -> &'static str {
+    SCHEMA_VERSION
+}
 
 /// Initializes the logging system for the pacts library.
 /// This should be called once at the start of your application.
@@ -76,7 +99,7 @@ mod tests {
         let envelope = Envelope::new(header, data);
         let schema_loader =
             SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
-        let mut validator = Validator::new(schema_loader);
+        let validator = Validator::new(schema_loader);
 
         let result = validator.validate(&envelope);
 
@@ -294,10 +317,10 @@ mod tests {
 
         assert_eq!(
             "schemas",
-            service.schema_loader().borrow().get_schema_root()
+            service.schema_loader().lock().unwrap().get_schema_root()
         );
-        assert_eq!("bees", service.schema_loader().borrow().get_domain());
-        assert_eq!("v1", service.schema_loader().borrow().get_version());
+        assert_eq!("bees", service.schema_loader().lock().unwrap().get_domain());
+        assert_eq!("v1", service.schema_loader().lock().unwrap().get_version());
     }
 
     #[test]
@@ -312,4 +335,4206 @@ mod tests {
         assert_eq!("player", header.schema_category());
         assert_eq!("player_request", header.schema_name());
     }
+
+    #[test]
+    fn test_validate_data_prefix_items() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let validator = Validator::new(schema_loader);
+
+        let schema = json!({
+            "type": "array",
+            "prefixItems": [
+                {"type": "string"},
+                {"type": "number"},
+                {"type": "boolean"}
+            ],
+            "items": {"type": "integer"}
+        });
+
+        let valid = validator.validate_data(&json!(["a", 1.5, true, 4, 5]), &schema);
+        assert!(valid.is_valid());
+
+        let result = validator.validate_data(&json!(["a", "b", true, 4.5]), &schema);
+        assert!(!result.is_valid());
+        assert_eq!(2, result.get_errors().len());
+        assert_eq!(
+            "Invalid type at index 1. Expected: number",
+            result.get_errors()[0]
+        );
+        assert_eq!(
+            "Invalid type at index 3. Expected: integer",
+            result.get_errors()[1]
+        );
+    }
+
+    #[test]
+    fn test_validate_data_fail_fast() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let mut validator = Validator::new(schema_loader);
+
+        let schema = json!({
+            "type": "object",
+            "required": ["slot", "material", "amount"],
+            "properties": {
+                "slot": {"type": "integer"}
+            }
+        });
+        let data = json!({"slot": "first"});
+
+        assert!(!validator.is_fail_fast());
+        let result = validator.validate_data(&data, &schema);
+        assert_eq!(3, result.get_errors().len());
+
+        validator.set_fail_fast(true);
+        let result = validator.validate_data(&data, &schema);
+        assert!(!result.is_valid());
+        assert_eq!(1, result.get_errors().len());
+        assert_eq!("Required field missing: material", result.get_errors()[0]);
+    }
+
+    #[test]
+    fn test_validate_ordered_batch_flags_out_of_order_envelope() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let data = json!({
+            "slot": 1,
+            "material": "Paper",
+            "amount": 2
+        });
+
+        let first = service.create_envelope(
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+            data.clone(),
+        );
+        let mut second = service.create_envelope(
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+            data.clone(),
+        );
+        let mut third =
+            service.create_envelope("inventory".to_string(), "inventory_item".to_string(), data);
+        second.header.timestamp = first.header.timestamp - chrono::Duration::hours(1);
+        third.header.timestamp = first.header.timestamp + chrono::Duration::seconds(1);
+
+        let results = service.validate_ordered_batch(&[first, second, third]);
+
+        assert_eq!(3, results.len());
+        assert!(results[0].is_valid());
+        assert!(!results[1].is_valid());
+        assert_eq!(
+            Some(&"Envelope out of order".to_string()),
+            results[1].get_errors().last()
+        );
+        assert_eq!(
+            Some(ValidationError::OUT_OF_ORDER),
+            results[1]
+                .get_error_details()
+                .last()
+                .map(|error| error.code)
+        );
+        assert_eq!(
+            Some("bees/v1/inventory/inventory_item"),
+            results[1].get_schema_ref()
+        );
+        assert!(results[2].is_valid());
+    }
+
+    #[test]
+    fn test_validate_data_whole_float_strict_integers() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let validator = Validator::new(schema_loader);
+
+        let schema = json!({"type": "integer"});
+
+        assert!(!validator.is_lenient_integers());
+        assert!(validator.validate_data(&json!(3), &schema).is_valid());
+        assert!(!validator.validate_data(&json!(3.0), &schema).is_valid());
+    }
+
+    #[test]
+    fn test_validate_data_whole_float_lenient_integers() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let mut validator = Validator::new(schema_loader);
+        validator.set_lenient_integers(true);
+
+        let schema = json!({"type": "integer"});
+
+        assert!(validator.validate_data(&json!(3.0), &schema).is_valid());
+        assert!(!validator.validate_data(&json!(3.5), &schema).is_valid());
+
+        assert_eq!(json!(3), validator.normalize(&json!(3.0), &schema));
+        assert_eq!(json!(-3), validator.normalize(&json!(-3.0), &schema));
+        assert_eq!(
+            json!(1u64 << 63),
+            validator.normalize(&json!(9223372036854775808.0), &schema)
+        );
+        assert_eq!(json!(1e300), validator.normalize(&json!(1e300), &schema));
+        assert_eq!(json!(-1e300), validator.normalize(&json!(-1e300), &schema));
+    }
+
+    #[test]
+    fn test_schema_loader_preload_all_after_clear() {
+        init_test_logging();
+
+        let mut schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        schema_loader.clear_cache();
+
+        let loaded = schema_loader.preload_all().unwrap();
+
+        assert!(loaded > 0);
+        assert_eq!(loaded, schema_loader.list_schemas().len());
+        assert!(schema_loader
+            .list_categories()
+            .contains(&"inventory".to_string()));
+        assert!(schema_loader.get_load_errors().is_empty());
+        let schema = schema_loader.load_schema("inventory", "inventory_item");
+        assert!(schema.is_object());
+    }
+
+    #[test]
+    fn test_schema_loader_preload_all_reuses_remote_archive() {
+        use std::io::Write;
+
+        init_test_logging();
+
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        archive
+            .start_file(
+                "schemas/bees/v1/inventory/cached_item.json",
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+        archive
+            .write_all(br#"{"type": "object", "required": ["slot"]}"#)
+            .unwrap();
+        let bytes = archive.finish().unwrap().into_inner();
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        assert_eq!(
+            1,
+            loader
+                .load_remote_archive(bytes, "http://127.0.0.1:9/schemas.zip")
+                .unwrap()
+        );
+        loader.clear_cache();
+        assert!(loader.load_schema_ref("inventory", "cached_item").is_none());
+
+        // The URL is unreachable, so this only succeeds if the kept archive is used
+        assert_eq!(1, loader.preload_all().unwrap());
+        assert_eq!(
+            json!({"type": "object", "required": ["slot"]}),
+            *loader.load_schema("inventory", "cached_item")
+        );
+    }
+
+    #[test]
+    fn test_schema_loader_resolve_version() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        assert_eq!(Some("v1".to_string()), schema_loader.resolve_version("v1"));
+        assert_eq!(Some("v1".to_string()), schema_loader.resolve_version("v99"));
+        assert_eq!(None, schema_loader.resolve_version("v0"));
+        assert_eq!(None, schema_loader.resolve_version("latest"));
+    }
+
+    #[test]
+    fn test_validate_negotiated_records_version() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let envelope = Envelope::new(
+            Header::new(
+                "v99".to_string(),
+                "inventory".to_string(),
+                "inventory_item".to_string(),
+            ),
+            json!({
+                "slot": 1,
+                "material": "Paper",
+                "amount": 2
+            }),
+        );
+
+        let result = service.validate_negotiated(&envelope);
+
+        assert!(result.is_valid());
+        assert_eq!(Some("v1"), result.get_schema_version());
+    }
+
+    #[test]
+    fn test_schema_loader_load_subschema() {
+        init_test_logging();
+
+        let mut schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let subschema = schema_loader
+            .load_schema_subschema("inventory", "inventory_item", "/properties/slot")
+            .unwrap();
+        assert_eq!(
+            schema_loader.load_schema("inventory", "inventory_item")["properties"]["slot"],
+            subschema
+        );
+
+        let missing =
+            schema_loader.load_schema_subschema("inventory", "inventory_item", "/properties/none");
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_validation_result_with_provenance() {
+        let result = ValidationResult::failure(vec!["External error".to_string()])
+            .with_schema_ref("bees/v1/player/player_request".to_string())
+            .with_schema_source("external-validator".to_string())
+            .with_schema_digest("abc123".to_string());
+
+        assert!(!result.is_valid());
+        assert_eq!(
+            Some("bees/v1/player/player_request"),
+            result.get_schema_ref()
+        );
+        assert_eq!(Some("external-validator"), result.get_schema_source());
+        assert_eq!(Some("abc123"), result.get_schema_digest());
+
+        let serialized = serde_json::to_value(&result).unwrap();
+        assert_eq!(json!(false), serialized["valid"]);
+        assert_eq!(json!(["External error"]), serialized["errors"]);
+        assert_eq!(
+            json!("bees/v1/player/player_request"),
+            serialized["schema_ref"]
+        );
+        assert_eq!(json!("external-validator"), serialized["schema_source"]);
+        assert_eq!(json!("abc123"), serialized["schema_digest"]);
+    }
+
+    #[test]
+    fn test_schema_loader_try_parsed_version() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        assert_eq!(Ok(1), schema_loader.try_parsed_version());
+
+        let malformed = SchemaLoader::new(
+            "schemas".to_string(),
+            "bees".to_string(),
+            "v1.2".to_string(),
+        );
+        assert!(malformed.try_parsed_version().is_err());
+        assert_eq!(1, malformed.get_parsed_version());
+    }
+
+    #[test]
+    fn test_validate_by_metadata_schema() {
+        use std::collections::HashMap;
+
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let mut metadata = HashMap::new();
+        metadata.insert("content_schema".to_string(), json!("inventory_item"));
+        let envelope = Envelope::with_metadata(
+            Header::new("v1".to_string(), "events".to_string(), "event".to_string()),
+            json!({
+                "slot": 1,
+                "material": "Paper",
+                "amount": 2
+            }),
+            metadata,
+        );
+
+        let result = service.validate_by_metadata_schema(&envelope, "inventory", "content_schema");
+
+        assert!(result.is_valid());
+        assert_eq!(
+            Some("bees/v1/inventory/inventory_item"),
+            result.get_schema_ref()
+        );
+    }
+
+    #[test]
+    fn test_validate_by_metadata_schema_missing_key() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let envelope = Envelope::new(
+            Header::new("v1".to_string(), "events".to_string(), "event".to_string()),
+            json!({}),
+        );
+
+        let result = service.validate_by_metadata_schema(&envelope, "inventory", "content_schema");
+
+        assert!(!result.is_valid());
+        assert_eq!(
+            "Metadata key 'content_schema' is required to select a schema",
+            result.get_errors()[0]
+        );
+    }
+
+    #[test]
+    fn test_validate_header_timestamp_clock_skew() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let validator =
+            Validator::new(schema_loader).with_max_clock_skew(chrono::Duration::minutes(5));
+
+        let mut envelope = Envelope::new(
+            Header::new(
+                "v1".to_string(),
+                "inventory".to_string(),
+                "inventory_item".to_string(),
+            ),
+            json!({
+                "slot": 1,
+                "material": "Paper",
+                "amount": 2
+            }),
+        );
+        assert!(validator.validate(&envelope).is_valid());
+
+        envelope.header.timestamp = chrono::Utc::now() + chrono::Duration::hours(3);
+        let result = validator.validate(&envelope);
+
+        assert!(!result.is_valid());
+        assert_eq!(
+            "Header timestamp is 3h in the future (max skew 5m)",
+            result.get_errors()[0]
+        );
+
+        envelope.header.timestamp = chrono::Utc::now() - chrono::Duration::days(400);
+        let result = validator.validate(&envelope);
+
+        assert_eq!(
+            "Header timestamp is 400d in the past (max skew 5m)",
+            result.get_errors()[0]
+        );
+    }
+
+    #[test]
+    fn test_header_with_ttl() {
+        let header = Header::new("v1".to_string(), "test".to_string(), "test".to_string())
+            .with_ttl(chrono::Duration::minutes(10));
+
+        assert_eq!(
+            Some(*header.timestamp() + chrono::Duration::minutes(10)),
+            header.expires_at().copied()
+        );
+    }
+
+    #[test]
+    fn test_header_deserializes_without_expires_at() {
+        let header: Header = serde_json::from_value(json!({
+            "schema_version": "v1",
+            "schema_category": "test",
+            "schema_name": "test",
+            "timestamp": "2025-01-01T00:00:00Z",
+            "content_type": null
+        }))
+        .unwrap();
+
+        assert!(header.expires_at().is_none());
+    }
+
+    #[test]
+    fn test_validate_expired_envelope() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let validator = Validator::new(schema_loader);
+
+        let mut header = Header::new(
+            "v1".to_string(),
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+        )
+        .with_ttl(chrono::Duration::minutes(10));
+        let data = json!({
+            "slot": 1,
+            "material": "Paper",
+            "amount": 2
+        });
+
+        let envelope = Envelope::new(header.clone(), data.clone());
+        assert!(validator.validate(&envelope).is_valid());
+
+        let expires_at = chrono::Utc::now() - chrono::Duration::minutes(1);
+        header.expires_at = Some(expires_at);
+        let result = validator.validate(&Envelope::new(header, data));
+
+        assert!(!result.is_valid());
+        assert_eq!(
+            format!("Envelope expired at {}", expires_at.to_rfc3339()),
+            result.get_errors()[0]
+        );
+    }
+
+    #[test]
+    fn test_envelope_redacted_by_schema() {
+        use std::collections::HashMap;
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "username": {"type": "string"},
+                "credentials": {
+                    "type": "object",
+                    "properties": {
+                        "key_id": {"type": "string"},
+                        "secret": {"type": "string", "writeOnly": true}
+                    }
+                }
+            }
+        });
+        let mut metadata = HashMap::new();
+        metadata.insert("auth_token".to_string(), json!("token-123"));
+        metadata.insert("source".to_string(), json!("gateway"));
+        let envelope = Envelope::with_metadata(
+            Header::new("v1".to_string(), "player".to_string(), "login".to_string()),
+            json!({
+                "username": "bee",
+                "credentials": {"key_id": "k1", "secret": "hunter2"}
+            }),
+            metadata,
+        );
+
+        let redacted = envelope.redacted_by_schema(&schema);
+
+        assert_eq!(
+            json!({
+                "username": "bee",
+                "credentials": {"key_id": "k1", "secret": "***"}
+            }),
+            *redacted.data()
+        );
+        let redacted_metadata = redacted.metadata().unwrap();
+        assert_eq!(json!("***"), redacted_metadata["auth_token"]);
+        assert_eq!(json!("gateway"), redacted_metadata["source"]);
+        assert_eq!(json!("hunter2"), envelope.data()["credentials"]["secret"]);
+    }
+
+    #[test]
+    fn test_validate_bytes() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let valid = service.validate_bytes(
+            br#"{"slot": 1, "material": "Paper", "amount": 2}"#,
+            "inventory",
+            "inventory_item",
+        );
+        assert!(valid.is_valid());
+
+        let malformed = service.validate_bytes(b"{\"slot\": 1,", "inventory", "inventory_item");
+        assert!(!malformed.is_valid());
+        assert!(malformed.get_errors()[0].starts_with("Invalid JSON: "));
+    }
+
+    #[test]
+    fn test_validate_with_content_type_schema() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let mut validator = Validator::new(schema_loader);
+        validator.register_content_type_schema(
+            "application/vnd.inventory.item+json".to_string(),
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+        );
+
+        let header = Header::with_content_type(
+            "v1".to_string(),
+            "events".to_string(),
+            "unmapped_event".to_string(),
+            "application/vnd.inventory.item+json".to_string(),
+        );
+        assert_eq!(
+            Some(("inventory", "inventory_item")),
+            validator.resolve_schema(&header)
+        );
+
+        let valid = Envelope::new(
+            header.clone(),
+            json!({
+                "slot": 1,
+                "material": "Paper",
+                "amount": 2
+            }),
+        );
+        assert!(validator.validate(&valid).is_valid());
+
+        let invalid = Envelope::new(header, json!({"slot": 1}));
+        let result = validator.validate(&invalid);
+        assert!(!result.is_valid());
+        assert_eq!("Required field missing: material", result.get_errors()[0]);
+    }
+
+    #[test]
+    fn test_validate_schema_document() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let validator = Validator::new(schema_loader);
+
+        let good = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "string"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        });
+        assert!(validator.validate_schema_document(&good).is_valid());
+
+        let malformed = json!({
+            "type": "objekt",
+            "required": "id",
+            "properties": {
+                "id": {"type": ["string", 5]},
+                "name": "string"
+            }
+        });
+        let result = validator.validate_schema_document(&malformed);
+
+        assert!(!result.is_valid());
+        assert_eq!(
+            vec![
+                "Unknown type 'objekt' at schema root",
+                "'required' must be an array of strings at schema root",
+                "'type' must be a string or an array of strings at /properties/id",
+                "Schema must be an object at /properties/name",
+            ],
+            result.get_errors()
+        );
+    }
+
+    #[test]
+    fn test_verify_all_schemas() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let results = service.verify_all_schemas();
+
+        let (_, inventory_item) = results
+            .iter()
+            .find(|(schema_ref, _)| schema_ref == "bees/v1/inventory/inventory_item")
+            .unwrap();
+        assert!(inventory_item.is_valid());
+
+        service.register_schema(
+            "inventory",
+            "broken_item",
+            json!({"type": "object", "properties": {"slot": {"type": "slot"}}}),
+        );
+
+        let results = service.verify_all_schemas();
+        let (_, broken_item) = results
+            .iter()
+            .find(|(schema_ref, _)| schema_ref == "bees/v1/inventory/broken_item")
+            .unwrap();
+        assert!(!broken_item.is_valid());
+        assert!(broken_item
+            .get_errors()
+            .iter()
+            .any(|error| error.contains("Unknown type 'slot'")));
+        assert!(results
+            .iter()
+            .find(|(schema_ref, _)| schema_ref == "bees/v1/inventory/inventory_item")
+            .unwrap()
+            .1
+            .is_valid());
+    }
+
+    #[test]
+    fn test_validate_compiled_matches_validate_data() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let validator = Validator::new(schema_loader);
+
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "position"],
+            "properties": {
+                "id": {"type": "string"},
+                "position": {
+                    "type": "array",
+                    "prefixItems": [{"type": "number"}, {"type": "number"}],
+                    "items": false
+                },
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        });
+        let compiled = validator.compile(&schema).unwrap();
+
+        for data in [
+            json!({"id": "a", "position": [1.0, 2.0], "tags": ["x"]}),
+            json!({"id": 7, "position": [1.0, "2", 3], "tags": ["x", 1]}),
+            json!({"tags": "x"}),
+            json!([1, 2]),
+        ] {
+            let expected = validator.validate_data(&data, &schema);
+            let actual = validator.validate_compiled(&data, &compiled);
+
+            assert_eq!(expected.is_valid(), actual.is_valid());
+            assert_eq!(expected.get_errors(), actual.get_errors());
+        }
+    }
+
+    #[test]
+    fn test_schema_load_error_not_found() {
+        init_test_logging();
+
+        let mut schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let error = schema_loader
+            .load_schema_version("v1", "inventory", "missing_schema")
+            .unwrap_err();
+
+        assert!(matches!(error, SchemaLoadError::NotFound(_)));
+        assert_eq!(
+            "Schema not found: bees/v1/inventory/missing_schema",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_schema_load_error_conversions() {
+        let json_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let error: SchemaLoadError = json_error.into();
+        assert!(matches!(error, SchemaLoadError::InvalidJson(_)));
+
+        let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        let error: SchemaLoadError = io_error.into();
+        assert!(matches!(error, SchemaLoadError::Io(_)));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn test_validate_data_top_level_string_max_length() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let validator = Validator::new(schema_loader);
+
+        let schema = json!({"type": "string", "maxLength": 8});
+
+        assert!(validator.validate_data(&json!("short"), &schema).is_valid());
+        // Multi-byte characters count once each.
+        assert!(validator
+            .validate_data(&json!("üüüüüüüü"), &schema)
+            .is_valid());
+
+        let blob = json!("a".repeat(1024));
+        let result = validator.validate_data(&blob, &schema);
+
+        assert!(!result.is_valid());
+        assert_eq!(
+            vec!["String length 1024 exceeds maxLength 8"],
+            result.get_errors()
+        );
+
+        let compiled = validator.compile(&schema).unwrap();
+        assert_eq!(
+            result.get_errors(),
+            validator.validate_compiled(&blob, &compiled).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_validate_data_nested_objects() {
+        let mut validator = Validator::standalone();
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "owner": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": {"type": "string"},
+                        "hive": {
+                            "type": "object",
+                            "required": ["id"],
+                            "properties": {"id": {"type": "integer"}}
+                        }
+                    }
+                }
+            }
+        });
+        let compiled = validator.compile(&schema).unwrap();
+
+        let valid = json!({"owner": {"name": "Queen", "hive": {"id": 7}}});
+        assert!(validator.validate_data(&valid, &schema).is_valid());
+        assert!(validator.validate_compiled(&valid, &compiled).is_valid());
+
+        let invalid = json!({"owner": {"hive": {"id": "seven"}}});
+        let expected = vec![
+            "Required field missing: name",
+            "Invalid type for field 'id'. Expected: integer",
+        ];
+        assert_eq!(
+            expected,
+            validator.validate_data(&invalid, &schema).get_errors()
+        );
+        assert_eq!(
+            expected,
+            validator
+                .validate_compiled(&invalid, &compiled)
+                .get_errors()
+        );
+
+        let limited = validator.validate_data_limited(&invalid, &schema, 1);
+        assert!(limited.is_truncated());
+        assert_eq!(vec!["Required field missing: name"], limited.get_errors());
+
+        validator.set_max_depth(2);
+        let expected = vec!["Maximum nesting depth 2 exceeded"];
+        let deep = json!({"owner": {"name": "Queen", "hive": {"id": "seven"}}});
+        assert_eq!(
+            expected,
+            validator.validate_data(&deep, &schema).get_errors()
+        );
+        assert_eq!(
+            expected,
+            validator.validate_compiled(&deep, &compiled).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_validate_data_nested_string_lengths() {
+        let validator = Validator::standalone();
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "code": {"type": "string", "maxLength": 2},
+                "tags": {"type": "array", "items": {"type": "string", "minLength": 3}}
+            }
+        });
+        let compiled = validator.compile(&schema).unwrap();
+
+        let valid = json!({"code": "ab", "tags": ["bee", "hive"]});
+        assert!(validator.validate_data(&valid, &schema).is_valid());
+        assert!(validator.validate_compiled(&valid, &compiled).is_valid());
+
+        let invalid = json!({"code": "abcdef", "tags": ["bee", "ok"]});
+        let expected = vec![
+            "String length 6 for field 'code' exceeds maxLength 2",
+            "String length 2 for field 'tags' at index 1 is below minLength 3",
+        ];
+        assert_eq!(
+            expected,
+            validator.validate_data(&invalid, &schema).get_errors()
+        );
+        assert_eq!(
+            expected,
+            validator
+                .validate_compiled(&invalid, &compiled)
+                .get_errors()
+        );
+    }
+
+    #[test]
+    fn test_schema_loader_list_schemas_and_categories() {
+        init_test_logging();
+
+        let schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let schemas = schema_loader.list_schemas();
+        let categories = schema_loader.list_categories();
+
+        assert!(schemas.contains(&("inventory".to_string(), "inventory_item".to_string())));
+        assert!(categories.contains(&"inventory".to_string()));
+        assert!(categories.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_validate_value_without_loader() {
+        let schema = json!({
+            "type": "object",
+            "required": ["slot"],
+            "properties": {"slot": {"type": "integer"}}
+        });
+
+        assert!(validate_value(&json!({"slot": 1}), &schema).is_valid());
+
+        let result = validate_value(&json!({"slot": "one"}), &schema);
+        assert!(!result.is_valid());
+        assert_eq!(
+            "Invalid type for field 'slot'. Expected: integer",
+            result.get_errors()[0]
+        );
+    }
+
+    #[test]
+    fn test_standalone_validator_validate_without_loader() {
+        let validator = Validator::standalone();
+
+        let envelope = Envelope::new(
+            Header::new(
+                "v1".to_string(),
+                "inventory".to_string(),
+                "inventory_item".to_string(),
+            ),
+            json!({}),
+        );
+        let result = validator.validate(&envelope);
+
+        assert!(!result.is_valid());
+        assert_eq!("No schema loader configured", result.get_errors()[0]);
+    }
+
+    #[test]
+    fn test_schema_loader_custom_schema_source() {
+        struct InMemorySource;
+
+        impl SchemaSource for InMemorySource {
+            fn fetch(
+                &self,
+                domain: &str,
+                version: &str,
+                category: &str,
+                name: &str,
+            ) -> Result<Option<serde_json::Value>, SchemaLoadError> {
+                match (domain, version, category, name) {
+                    ("bees", "v1", "hive", "hive_status") => Ok(Some(json!({
+                        "type": "object",
+                        "required": ["population"]
+                    }))),
+                    _ => Ok(None),
+                }
+            }
+        }
+
+        init_test_logging();
+
+        let mut schema_loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        schema_loader.set_schema_source(Box::new(InMemorySource));
+
+        let schema = schema_loader.load_schema("hive", "hive_status");
+        assert_eq!(json!(["population"]), schema["required"]);
+
+        let missing = schema_loader.load_schema_version("v1", "hive", "unknown");
+        assert!(matches!(missing, Err(SchemaLoadError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_validate_schema_document_duplicate_required() {
+        let validator = Validator::standalone();
+
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "name", "id"],
+            "properties": {
+                "id": {"type": "string"},
+                "name": {"type": "string"}
+            }
+        });
+        let result = validator.validate_schema_document(&schema);
+
+        assert!(!result.is_valid());
+        assert_eq!(
+            vec!["Duplicate entry in required: id at schema root"],
+            result.get_errors()
+        );
+    }
+
+    #[test]
+    fn test_validate_data_enum() {
+        let validator = Validator::standalone();
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "request_type": {"enum": ["PLAYER_JOIN", "PLAYER_LEAVE"]}
+            }
+        });
+
+        assert!(validator
+            .validate_data(&json!({"request_type": "PLAYER_JOIN"}), &schema)
+            .is_valid());
+
+        let result = validator.validate_data(&json!({"request_type": "PLAYER_KICK"}), &schema);
+        assert_eq!(
+            vec![
+                "Invalid value for field 'request_type'. Expected one of: [\"PLAYER_JOIN\",\"PLAYER_LEAVE\"]"
+            ],
+            result.get_errors()
+        );
+    }
+
+    #[test]
+    fn test_validate_data_float_enum_epsilon() {
+        let mut validator = Validator::standalone();
+
+        let schema = json!({"type": "number", "enum": [0.1, 0.2, 0.3]});
+        let data = json!(0.1 + 0.2);
+
+        assert!(!validator.validate_data(&data, &schema).is_valid());
+
+        validator.set_float_enum_epsilon(1e-9);
+        assert!(validator.validate_data(&data, &schema).is_valid());
+        assert!(!validator.validate_data(&json!(0.31), &schema).is_valid());
+
+        let compiled = validator.compile(&schema).unwrap();
+        assert!(validator.validate_compiled(&data, &compiled).is_valid());
+    }
+
+    #[test]
+    fn test_validation_observer() {
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        init_test_logging();
+
+        struct LoggingObserver {
+            events: Arc<Mutex<Vec<(String, bool)>>>,
+        }
+
+        impl ValidationObserver for LoggingObserver {
+            fn on_validate(&self, schema: &str, result: &ValidationResult, elapsed: Duration) {
+                log::info!(
+                    "Validated {} in {:?}: {}",
+                    schema,
+                    elapsed,
+                    if result.is_valid() { "pass" } else { "fail" }
+                );
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push((schema.to_string(), result.is_valid()));
+            }
+        }
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.set_observer(Box::new(LoggingObserver {
+            events: events.clone(),
+        }));
+
+        service.validate_data(&json!({"slot": 1}), "inventory", "inventory_item");
+        service.validate_data(&json!({"slot": "one"}), "inventory", "inventory_item");
+
+        let events = events.lock().unwrap();
+        assert_eq!(2, events.len());
+        assert_eq!("bees/v1/inventory/inventory_item", events[0].0);
+        assert!(!events[1].1);
+    }
+
+    #[test]
+    fn test_envelope_diff() {
+        let header = Header::new(
+            "v1".to_string(),
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+        );
+        let before = Envelope::new(
+            header.clone(),
+            json!({"slot": 1, "item": {"material": "STONE", "amount": 64}, "lore": "old"}),
+        );
+        let after = Envelope::new(
+            header,
+            json!({"slot": 1, "item": {"material": "DIRT", "amount": 64}, "owner/id": 7}),
+        );
+
+        let changes = before.diff(&after);
+        assert_eq!(
+            vec![
+                FieldChange {
+                    path: "/item/material".to_string(),
+                    old: Some(json!("STONE")),
+                    new: Some(json!("DIRT")),
+                },
+                FieldChange {
+                    path: "/lore".to_string(),
+                    old: Some(json!("old")),
+                    new: None,
+                },
+                FieldChange {
+                    path: "/owner~1id".to_string(),
+                    old: None,
+                    new: Some(json!(7)),
+                },
+            ],
+            changes
+        );
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn test_send_normalized_data_fills_defaults() {
+        init_test_logging();
+
+        struct DefaultsSource;
+
+        impl SchemaSource for DefaultsSource {
+            fn fetch(
+                &self,
+                _domain: &str,
+                _version: &str,
+                category: &str,
+                name: &str,
+            ) -> Result<Option<serde_json::Value>, SchemaLoadError> {
+                match (category, name) {
+                    ("hive", "hive_status") => Ok(Some(json!({
+                        "type": "object",
+                        "required": ["population", "queen_present"],
+                        "properties": {
+                            "population": {"type": "integer"},
+                            "queen_present": {"type": "boolean", "default": true}
+                        }
+                    }))),
+                    _ => Ok(None),
+                }
+            }
+        }
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service
+            .schema_loader()
+            .lock()
+            .unwrap()
+            .set_schema_source(Box::new(DefaultsSource));
+
+        let sent = service.send_normalized_data(
+            "hive".to_string(),
+            "hive_status".to_string(),
+            json!({"population": 1200}),
+            |envelope| Ok(envelope.data().clone()),
+        );
+        assert_eq!(Ok(json!({"population": 1200, "queen_present": true})), sent);
+
+        let missing = service.send_normalized_data(
+            "hive".to_string(),
+            "hive_status".to_string(),
+            json!({}),
+            |envelope| Ok(envelope.data().clone()),
+        );
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_header_from_str() {
+        let header: Header = "v1/player/join".parse().unwrap();
+        assert_eq!("v1", header.schema_version());
+        assert_eq!("player", header.schema_category());
+        assert_eq!("join", header.schema_name());
+
+        assert_eq!(
+            Err(ParseHeaderError::WrongSegmentCount {
+                reference: "v1/player".to_string(),
+                found: 2,
+            }),
+            "v1/player".parse::<Header>()
+        );
+        assert_eq!(
+            Err(ParseHeaderError::EmptySegment {
+                reference: "v1//join".to_string(),
+                segment: "category",
+            }),
+            "v1//join".parse::<Header>()
+        );
+        assert_eq!(
+            "Invalid schema reference 'bees/v1/player/join': expected version/category/name, found 4 segment(s)",
+            "bees/v1/player/join".parse::<Header>().unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_envelope_redacted() {
+        let header = Header::new(
+            "v1".to_string(),
+            "player".to_string(),
+            "player_login".to_string(),
+        );
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("auth_token".to_string(), json!("abc123"));
+        metadata.insert("_signature".to_string(), json!("sig"));
+        metadata.insert("trace_id".to_string(), json!("t-1"));
+
+        let envelope = Envelope::with_metadata(
+            header,
+            json!({
+                "username": "steve",
+                "Password": "hunter2",
+                "keys": [{"client_secret": "s3cr3t", "id": 1}]
+            }),
+            metadata,
+        );
+
+        let redacted = envelope.redacted();
+        assert_eq!(
+            json!({
+                "username": "steve",
+                "Password": "***",
+                "keys": [{"client_secret": "***", "id": 1}]
+            }),
+            redacted.data
+        );
+        let redacted_metadata = redacted.metadata().unwrap();
+        assert_eq!(json!("***"), redacted_metadata["auth_token"]);
+        assert_eq!(json!("***"), redacted_metadata["_signature"]);
+        assert_eq!(json!("t-1"), redacted_metadata["trace_id"]);
+
+        let custom = envelope.redacted_with_keys(&["trace_id"]);
+        assert_eq!(json!("abc123"), custom.metadata().unwrap()["auth_token"]);
+        assert_eq!(json!("***"), custom.metadata().unwrap()["trace_id"]);
+        assert_eq!("hunter2", envelope.data["Password"]);
+    }
+
+    #[test]
+    fn test_schema_loader_load_schema_by_id() {
+        init_test_logging();
+
+        struct WaspsSource;
+
+        impl SchemaSource for WaspsSource {
+            fn fetch(
+                &self,
+                domain: &str,
+                version: &str,
+                category: &str,
+                name: &str,
+            ) -> Result<Option<serde_json::Value>, SchemaLoadError> {
+                match (domain, version, category, name) {
+                    ("wasps", "v2", "nest", "nest_status") => Ok(Some(json!({"type": "object"}))),
+                    _ => Ok(None),
+                }
+            }
+        }
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        loader.set_schema_source(Box::new(WaspsSource));
+
+        let schema = loader
+            .load_schema_by_id("wasps/v2/nest/nest_status")
+            .unwrap();
+        assert_eq!(json!({"type": "object"}), *schema);
+        assert_eq!("bees", loader.get_domain());
+        assert_eq!("v1", loader.get_version());
+
+        for id in [
+            "bees/v1/player",
+            "bees/v1/player/player_request/extra",
+            "bees//player/x",
+        ] {
+            let error = loader.load_schema_by_id(id).unwrap_err();
+            assert!(matches!(error, SchemaLoadError::InvalidId(_)));
+        }
+        assert_eq!(
+            "Invalid schema id 'bees/v1/player': expected domain/version/category/name",
+            loader
+                .load_schema_by_id("bees/v1/player")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_validate_data_require_sorted_keys() {
+        let mut validator = Validator::standalone();
+        validator.set_require_sorted_keys(true);
+
+        let schema = json!({"type": "object"});
+        let sorted = json!({"amount": 64, "material": "STONE", "slot": {"x": 1, "y": 2}});
+        assert!(validator.validate_data(&sorted, &schema).is_valid());
+
+        let unsorted: serde_json::Value =
+            serde_json::from_str(r#"{"slot": 1, "amount": 64}"#).unwrap();
+        assert_eq!(
+            vec!["Object keys are not in canonical (sorted) order"],
+            validator.validate_data(&unsorted, &schema).get_errors()
+        );
+
+        let nested_unsorted: serde_json::Value =
+            serde_json::from_str(r#"{"amount": 64, "slot": {"y": 2, "x": 1}}"#).unwrap();
+        assert!(!validator
+            .validate_data(&nested_unsorted, &schema)
+            .is_valid());
+
+        validator.set_require_sorted_keys(false);
+        assert!(validator.validate_data(&unsorted, &schema).is_valid());
+
+        let mut nested = json!({"leaf": true});
+        for _ in 0..6 {
+            nested = json!({"inner": nested});
+        }
+        validator.set_require_sorted_keys(true);
+        validator.set_max_depth(3);
+        assert_eq!(
+            vec!["Maximum nesting depth 3 exceeded"],
+            validator.validate_data(&nested, &schema).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_pacts_service_export_catalog() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let catalog = service.export_catalog();
+        assert_eq!("bees", catalog.domain);
+
+        let schema = service
+            .schema_loader()
+            .lock()
+            .unwrap()
+            .load_schema("inventory", "inventory_item");
+        let entry = catalog
+            .find("v1", "inventory", "inventory_item")
+            .expect("inventory_item should be listed");
+        assert_eq!(schema_digest(&schema), entry.digest);
+        assert_eq!(64, entry.digest.len());
+        assert_eq!(
+            schema.get("title").and_then(|t| t.as_str()),
+            entry.title.as_deref()
+        );
+    }
+
+    #[test]
+    fn test_schema_digest_is_stable() {
+        let a = json!({"type": "object", "required": ["slot"]});
+        let b = json!({"required": ["slot"], "type": "object"});
+        assert_eq!(schema_digest(&a), schema_digest(&b));
+        assert_ne!(schema_digest(&a), schema_digest(&json!({"type": "string"})));
+    }
+
+    #[test]
+    fn test_multi_domain_loader() {
+        init_test_logging();
+
+        struct WyrmlingsSource;
+
+        impl SchemaSource for WyrmlingsSource {
+            fn fetch(
+                &self,
+                domain: &str,
+                version: &str,
+                category: &str,
+                name: &str,
+            ) -> Result<Option<serde_json::Value>, SchemaLoadError> {
+                match (domain, version, category, name) {
+                    ("wyrmlings", "v1", "lair", "lair_status") => {
+                        Ok(Some(json!({"type": "object", "required": ["hoard"]})))
+                    }
+                    _ => Ok(None),
+                }
+            }
+        }
+
+        let loader = SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let mut multi = MultiDomainLoader::from_loader(loader);
+        multi.set_schema_source(Box::new(WyrmlingsSource));
+
+        let bees = multi
+            .load_schema("bees", "v1", "inventory", "inventory_item")
+            .unwrap();
+        assert!(bees.is_object());
+
+        let wyrmlings = multi
+            .load_schema("wyrmlings", "v1", "lair", "lair_status")
+            .unwrap();
+        assert_eq!(json!({"type": "object", "required": ["hoard"]}), *wyrmlings);
+
+        let domains = multi.list_domains();
+        assert!(domains.contains(&"bees".to_string()));
+        assert!(domains.contains(&"wyrmlings".to_string()));
+        assert_eq!(
+            vec![("lair".to_string(), "lair_status".to_string())],
+            multi.list_schemas("wyrmlings", "v1")
+        );
+
+        assert!(matches!(
+            multi.load_schema("wyrmlings", "v1", "lair", "missing"),
+            Err(SchemaLoadError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_data_max_errors() {
+        let mut validator = Validator::standalone();
+        validator.set_max_errors(2);
+
+        let schema = json!({"type": "array", "items": {"type": "integer"}});
+        let data = json!(["a", "b", "c", "d", "e"]);
+
+        let result = validator.validate_data(&data, &schema);
+        assert!(!result.is_valid());
+        assert_eq!(
+            vec![
+                "Invalid type at index 0. Expected: integer",
+                "Invalid type at index 1. Expected: integer",
+                "... and 3 more errors",
+            ],
+            result.get_errors()
+        );
+
+        let compiled = validator.compile(&schema).unwrap();
+        assert_eq!(
+            result.get_errors(),
+            validator.validate_compiled(&data, &compiled).get_errors()
+        );
+
+        validator.set_max_errors(0);
+        let result = validator.validate_data(&data, &schema);
+        assert!(!result.is_valid());
+        assert_eq!(vec!["... and 5 more errors"], result.get_errors());
+
+        assert!(validator.validate_data(&json!([1, 2]), &schema).is_valid());
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn test_pacts_service_validate_by_url() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        init_test_logging();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            // Serves a single request; later lookups must hit the cache
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            let body = r#"{"type": "object", "required": ["hive_id"]}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        let mut service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.set_allowed_schema_hosts(vec!["127.0.0.1".to_string()]);
+
+        let url = format!("http://127.0.0.1:{}/schemas/hive.json", port);
+        let envelope_with = |data: serde_json::Value, url: &str| {
+            let mut metadata = std::collections::HashMap::new();
+            metadata.insert("schema_url".to_string(), json!(url));
+            Envelope::with_metadata(
+                Header::new("v1".to_string(), "hive".to_string(), "hive".to_string()),
+                data,
+                metadata,
+            )
+        };
+
+        let valid =
+            service.validate_by_url(&envelope_with(json!({"hive_id": 7}), &url), "schema_url");
+        assert!(valid.is_valid(), "{:?}", valid.get_errors());
+        assert_eq!(Some(url.as_str()), valid.get_schema_ref());
+        server.join().unwrap();
+
+        let invalid = service.validate_by_url(&envelope_with(json!({}), &url), "schema_url");
+        assert_eq!(
+            vec!["Required field missing: hive_id"],
+            invalid.get_errors()
+        );
+
+        let blocked = service.validate_by_url(
+            &envelope_with(json!({}), "http://169.254.169.254/latest/meta-data"),
+            "schema_url",
+        );
+        assert_eq!(
+            vec!["Schema URL host is not allowed: 169.254.169.254"],
+            blocked.get_errors()
+        );
+    }
+
+    #[test]
+    fn test_validator_regex_cache() {
+        let validator = Validator::standalone();
+        assert_eq!(0, validator.regex_cache_len());
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "material": {"type": "string", "pattern": "^[A-Z_]+$"}
+            }
+        });
+
+        assert!(validator
+            .validate_data(&json!({"material": "OAK_LOG"}), &schema)
+            .is_valid());
+        assert_eq!(
+            vec!["Value for field 'material' does not match pattern: ^[A-Z_]+$"],
+            validator
+                .validate_data(&json!({"material": "oak log"}), &schema)
+                .get_errors()
+        );
+        assert_eq!(1, validator.regex_cache_len());
+
+        validator.clear_regex_cache();
+        assert_eq!(0, validator.regex_cache_len());
+    }
+
+    #[test]
+    fn test_validation_result_to_pretty_string() {
+        assert_eq!("✓ Valid", ValidationResult::success().to_pretty_string());
+
+        let result = ValidationResult::failure(vec![
+            "Required field missing: slot".to_string(),
+            "Invalid type for field 'amount'. Expected: integer".to_string(),
+        ]);
+        assert_eq!(
+            "✗ Required field missing: slot\n✗ Invalid type for field 'amount'. Expected: integer\n2 errors",
+            result.to_pretty_string()
+        );
+        assert_eq!(
+            "Required field missing: slot; Invalid type for field 'amount'. Expected: integer",
+            result.error_message()
+        );
+
+        let single = ValidationResult::failure(vec!["Header is required".to_string()]);
+        assert_eq!("✗ Header is required\n1 error", single.to_pretty_string());
+    }
+
+    #[test]
+    fn test_pacts_service_validate_files() {
+        let dir = std::env::temp_dir().join(format!("pacts-validate-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let schema_path = dir.join("inventory_item.json");
+        let data_path = dir.join("data.json");
+        let broken_path = dir.join("broken.json");
+        std::fs::write(
+            &schema_path,
+            r#"{"type": "object", "required": ["slot"], "properties": {"slot": {"type": "integer"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(&data_path, r#"{"slot": "first"}"#).unwrap();
+        std::fs::write(&broken_path, "{\"slot\":").unwrap();
+
+        let result = PactsService::validate_files(&schema_path, &data_path).unwrap();
+        assert_eq!(
+            vec!["Invalid type for field 'slot'. Expected: integer"],
+            result.get_errors()
+        );
+
+        assert!(matches!(
+            PactsService::validate_files(&dir.join("missing.json"), &data_path),
+            Err(FileValidationError::SchemaUnreadable { .. })
+        ));
+        assert!(matches!(
+            PactsService::validate_files(&broken_path, &data_path),
+            Err(FileValidationError::SchemaInvalid { .. })
+        ));
+        assert!(matches!(
+            PactsService::validate_files(&schema_path, &dir.join("missing.json")),
+            Err(FileValidationError::DataUnreadable { .. })
+        ));
+        assert!(matches!(
+            PactsService::validate_files(&schema_path, &broken_path),
+            Err(FileValidationError::DataInvalid { .. })
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pacts_service_validate_nested() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let inner = service.create_envelope(
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+            json!({"slot": "first", "material": "STONE", "amount": 64}),
+        );
+        let outer = service.create_envelope(
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+            serde_json::to_value(&inner).unwrap(),
+        );
+
+        let inner_error = "data.Invalid type for field 'slot'. Expected: integer".to_string();
+
+        let nested = service.validate_nested(&outer, 1);
+        assert!(!nested.is_valid());
+        assert!(nested.get_errors().contains(&inner_error));
+        assert_eq!(nested.errors.len(), nested.error_codes.len());
+        assert!(nested.get_error_details().contains(&ValidationError::new(
+            ValidationError::TYPE_MISMATCH,
+            inner_error.clone()
+        )));
+
+        let shallow = service.validate_nested(&outer, 0);
+        assert!(!shallow.get_errors().contains(&inner_error));
+    }
+
+    #[test]
+    fn test_validate_data_includes_property_description() {
+        let validator = Validator::standalone();
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "age": {"type": "integer", "description": "Player age in years"},
+                "name": {"type": "string"}
+            }
+        });
+        let data = json!({"age": "old", "name": 7});
+
+        let expected = vec![
+            "Invalid type for field 'age'. Expected: integer (Player age in years)",
+            "Invalid type for field 'name'. Expected: string",
+        ];
+        assert_eq!(
+            expected,
+            validator.validate_data(&data, &schema).get_errors()
+        );
+
+        let compiled = validator.compile(&schema).unwrap();
+        assert_eq!(
+            expected,
+            validator.validate_compiled(&data, &compiled).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_validate_data_local_ref() {
+        let validator = Validator::standalone();
+
+        let schema = json!({
+            "type": "object",
+            "definitions": {
+                "position": {"type": "object", "description": "Block position"}
+            },
+            "properties": {
+                "spawn": {"$ref": "#/definitions/position"}
+            }
+        });
+
+        assert!(validator
+            .validate_data(&json!({"spawn": {"x": 1, "y": 2}}), &schema)
+            .is_valid());
+        assert_eq!(
+            vec!["Invalid type for field 'spawn'. Expected: object (Block position)"],
+            validator
+                .validate_data(&json!({"spawn": [1, 2]}), &schema)
+                .get_errors()
+        );
+
+        let missing = json!({"properties": {"spawn": {"$ref": "#/definitions/missing"}}});
+        assert!(validator.validate_data(&json!({}), &missing).is_valid());
+        assert_eq!(
+            vec!["Unresolved $ref: #/definitions/missing"],
+            validator
+                .validate_data(&json!({"spawn": {}}), &missing)
+                .get_errors()
+        );
+        assert_eq!(
+            ValidationError::new(
+                ValidationError::REF_UNRESOLVED,
+                "Unresolved $ref: #/definitions/missing".to_string()
+            ),
+            validator.compile(&missing).unwrap_err()
+        );
+
+        let recursive = json!({"definitions": {"node": {"$ref": "#/definitions/node"}}, "$ref": "#/definitions/node"});
+        assert!(!validator.validate_data(&json!({}), &recursive).is_valid());
+        assert_eq!(
+            ValidationError::new(
+                ValidationError::REF_UNRESOLVED,
+                "$ref nesting too deep at: #/definitions/node".to_string()
+            ),
+            validator.compile(&recursive).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_data_recursive_ref() {
+        let validator = Validator::standalone();
+
+        let tree = json!({"type": "array", "items": {"$ref": "#"}});
+        let compiled_tree = validator.compile(&tree).unwrap();
+        let nested = json!([[[]], [], [[[[]]]]]);
+        assert!(validator.validate_data(&nested, &tree).is_valid());
+        assert!(validator
+            .validate_compiled(&nested, &compiled_tree)
+            .is_valid());
+
+        let expected = vec!["Invalid type at index 1. Expected: array"];
+        let invalid = json!([[[], 1]]);
+        assert_eq!(
+            expected,
+            validator.validate_data(&invalid, &tree).get_errors()
+        );
+        assert_eq!(
+            expected,
+            validator
+                .validate_compiled(&invalid, &compiled_tree)
+                .get_errors()
+        );
+
+        // Each definition refers to the next one twice; expanding every $ref up
+        // front would build 2^30 copies of the last one
+        let mut defs = serde_json::Map::new();
+        for index in 0..30 {
+            let next = format!("#/$defs/d{}", index + 1);
+            defs.insert(
+                format!("d{}", index),
+                json!({"type": "object", "properties": {"a": {"$ref": next}, "b": {"$ref": next}}}),
+            );
+        }
+        defs.insert("d30".to_string(), json!({"type": "integer"}));
+        let fan_out = json!({"$defs": defs, "$ref": "#/$defs/d0"});
+        let data = json!({"a": {"b": {"a": {}}}});
+        assert!(validator.validate_data(&data, &fan_out).is_valid());
+        let compiled_fan_out = validator.compile(&fan_out).unwrap();
+        assert!(validator
+            .validate_compiled(&data, &compiled_fan_out)
+            .is_valid());
+        assert!(!validator
+            .validate_compiled(&json!({"a": "one"}), &compiled_fan_out)
+            .is_valid());
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn test_validate_data_remote_ref() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            // Serves a single request; later lookups must hit the cache
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            let body = r#"{"definitions": {"address": {"type": "object"}}}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        let mut validator = Validator::standalone();
+        validator.set_allowed_ref_hosts(vec!["127.0.0.1".to_string()]);
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "$ref": format!("http://127.0.0.1:{}/common/Address.json#/definitions/address", port)
+                }
+            }
+        });
+
+        assert!(validator
+            .validate_data(&json!({"address": {"city": "Hive"}}), &schema)
+            .is_valid());
+        server.join().unwrap();
+
+        assert_eq!(
+            vec!["Invalid type for field 'address'. Expected: object"],
+            validator
+                .validate_data(&json!({"address": "Hive"}), &schema)
+                .get_errors()
+        );
+
+        let blocked = json!({"$ref": "http://169.254.169.254/Address.json#/definitions/address"});
+        assert_eq!(
+            vec!["Schema URL host is not allowed: 169.254.169.254"],
+            validator.validate_data(&json!({}), &blocked).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_validator_validate_header() {
+        let validator = Validator::standalone();
+
+        let header = Header::new(
+            "v1".to_string(),
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+        );
+        assert!(validator.validate_header(&header).is_valid());
+
+        let partial = Header::new("v1".to_string(), String::new(), String::new());
+        assert_eq!(
+            vec![
+                "Schema category is required in header",
+                "Schema name is required in header"
+            ],
+            validator.validate_header(&partial).get_errors()
+        );
+
+        let empty = Header::new(String::new(), String::new(), String::new());
+        assert_eq!(
+            vec!["Header is required"],
+            validator.validate_header(&empty).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_schema_loader_load_schema_ref() {
+        init_test_logging();
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        let borrowed = loader
+            .load_schema_ref("inventory", "inventory_item")
+            .expect("inventory_item should be cached")
+            .clone();
+        assert_eq!(*loader.load_schema("inventory", "inventory_item"), borrowed);
+        assert!(std::ptr::eq(
+            loader
+                .load_schema_ref("inventory", "inventory_item")
+                .unwrap(),
+            loader
+                .load_schema_ref("inventory", "inventory_item")
+                .unwrap()
+        ));
+        assert!(loader.load_schema_ref("inventory", "missing").is_none());
+    }
+
+    #[test]
+    fn test_envelope_from_body() {
+        let header_for = |content_type: &str| {
+            Header::with_content_type(
+                "v1".to_string(),
+                "inventory".to_string(),
+                "inventory_item".to_string(),
+                content_type.to_string(),
+            )
+        };
+        let expected = json!({"slot": 1, "material": "STONE", "amount": 64});
+
+        let from_json = Envelope::from_body(
+            header_for("application/json; charset=utf-8"),
+            br#"{"slot": 1, "material": "STONE", "amount": 64}"#,
+        )
+        .unwrap();
+        assert_eq!(expected, from_json.data);
+
+        let from_yaml = Envelope::from_body(
+            header_for("application/yaml"),
+            b"slot: 1\nmaterial: STONE\namount: 64\n",
+        )
+        .unwrap();
+        assert_eq!(expected, from_yaml.data);
+
+        let msgpack = rmp_serde::to_vec_named(&expected).unwrap();
+        let from_msgpack =
+            Envelope::from_body(header_for("application/msgpack"), &msgpack).unwrap();
+        assert_eq!(expected, from_msgpack.data);
+
+        assert_eq!(
+            "Unsupported content type: text/csv",
+            Envelope::from_body(header_for("text/csv"), b"1,STONE,64").unwrap_err()
+        );
+        assert!(Envelope::from_body(header_for("application/json"), b"{")
+            .unwrap_err()
+            .starts_with("Invalid application/json body: "));
+    }
+
+    #[test]
+    fn test_pacts_service_validate_metadata() {
+        init_test_logging();
+
+        let mut service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let envelope = service.create_envelope(
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+            json!({"slot": 1, "material": "STONE", "amount": 64}),
+        );
+        assert!(service.validate_metadata(&envelope).is_valid());
+
+        service.set_metadata_schema(json!({
+            "type": "object",
+            "required": ["source"],
+            "properties": {
+                "source": {"type": "string"},
+                "priority": {"type": "integer"}
+            }
+        }));
+
+        assert_eq!(
+            vec!["Required field missing: source"],
+            service.validate_metadata(&envelope).get_errors()
+        );
+
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("source".to_string(), json!("hive-7"));
+        metadata.insert("priority".to_string(), json!("high"));
+        let with_metadata =
+            Envelope::with_metadata(envelope.header.clone(), envelope.data, metadata);
+        assert_eq!(
+            vec!["Invalid type for field 'priority'. Expected: integer"],
+            service.validate_metadata(&with_metadata).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_pacts_service_missing_schema_policy() {
+        init_test_logging();
+
+        let mut service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        assert_eq!(MissingSchemaPolicy::Fail, service.missing_schema_policy());
+
+        let envelope = service.create_envelope(
+            "migration".to_string(),
+            "unpublished_schema".to_string(),
+            json!({"anything": true}),
+        );
+
+        let failed = service.validate(&envelope);
+        assert_eq!(
+            vec!["Schema not found: bees/v1/migration/unpublished_schema"],
+            failed.get_errors()
+        );
+
+        service.set_missing_schema_policy(MissingSchemaPolicy::Warn);
+        let warned = service.validate(&envelope);
+        assert!(warned.is_valid());
+        assert_eq!(
+            vec!["Passing envelope without validation: Schema not found: bees/v1/migration/unpublished_schema"],
+            warned.get_warnings()
+        );
+
+        service.set_missing_schema_policy(MissingSchemaPolicy::Skip);
+        let skipped = service.validate(&envelope);
+        assert!(skipped.is_valid());
+        assert!(skipped.get_warnings().is_empty());
+
+        let invalid = service.create_envelope(
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+            json!({"slot": "first", "material": "STONE", "amount": 64}),
+        );
+        assert!(!service.validate(&invalid).is_valid());
+    }
+
+    #[test]
+    fn test_envelope_partial_eq() {
+        let header = Header::new(
+            "v1".to_string(),
+            "inventory".to_string(),
+            "inventory_item".to_string(),
+        );
+        let envelope = Envelope::new(header.clone(), json!({"slot": 1}));
+
+        let round_tripped: Envelope =
+            serde_json::from_str(&serde_json::to_string(&envelope).unwrap()).unwrap();
+        assert_eq!(envelope, round_tripped);
+
+        assert_ne!(envelope, Envelope::new(header, json!({"slot": 2})));
+    }
+
+    #[test]
+    fn test_validate_schema_keywords() {
+        let validator = Validator::standalone();
+
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "material": {"type": "string", "pattern": "([A-Z", "maxLength": -1},
+                "rank": {"enum": "gold"}
+            }
+        });
+
+        let result = validator.validate_schema(&schema);
+        assert_eq!(
+            vec![
+                "'pattern' is not a valid regular expression at /properties/material",
+                "'maxLength' must be a non-negative integer at /properties/material",
+                "'enum' must be an array at /properties/rank",
+            ],
+            result.get_errors()
+        );
+        assert_eq!(
+            result.get_errors(),
+            validator.validate_schema_document(&schema).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_schema_loader_validate_on_load() {
+        init_test_logging();
+
+        struct MalformedSource;
+
+        impl SchemaSource for MalformedSource {
+            fn fetch(
+                &self,
+                _domain: &str,
+                _version: &str,
+                _category: &str,
+                _name: &str,
+            ) -> Result<Option<serde_json::Value>, SchemaLoadError> {
+                Ok(Some(json!({"type": "object", "required": "slot"})))
+            }
+        }
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        loader.set_schema_source(Box::new(MalformedSource));
+        assert!(loader.load_schema_version("v1", "hive", "lenient").is_ok());
+
+        loader.set_validate_on_load(true);
+        let error = loader
+            .load_schema_version("v1", "hive", "strict")
+            .unwrap_err();
+        assert_eq!(
+            "Malformed schema bees/v1/hive/strict: 'required' must be an array of strings at schema root",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_validator_supported_keywords() {
+        let keywords = Validator::supported_keywords();
+        for keyword in ["type", "required", "properties", "enum", "pattern", "$ref"] {
+            assert!(keywords.contains(&keyword), "missing {}", keyword);
+        }
+
+        let schema = json!({"type": "integer", "minimum": 1, "description": "Amount"});
+        let unsupported: Vec<&str> = schema
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .filter(|keyword| !keywords.contains(keyword) && *keyword != "description")
+            .collect();
+        assert_eq!(vec!["minimum"], unsupported);
+    }
+
+    #[test]
+    fn test_schema_loader_schema_version_of() {
+        init_test_logging();
+
+        struct VersionedSource;
+
+        impl SchemaSource for VersionedSource {
+            fn fetch(
+                &self,
+                _domain: &str,
+                _version: &str,
+                _category: &str,
+                name: &str,
+            ) -> Result<Option<serde_json::Value>, SchemaLoadError> {
+                Ok(match name {
+                    "player_request" => {
+                        Some(json!({"$id": "bees/v2/player/player_request", "version": 3}))
+                    }
+                    "player_join" => Some(json!({"version": 2})),
+                    "player_leave" => Some(json!({"type": "object"})),
+                    _ => None,
+                })
+            }
+        }
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        loader.set_schema_source(Box::new(VersionedSource));
+
+        assert_eq!(
+            Some(2),
+            loader.schema_version_of("player", "player_request")
+        );
+        assert_eq!(Some(2), loader.schema_version_of("player", "player_join"));
+        assert_eq!(None, loader.schema_version_of("player", "player_leave"));
+        assert_eq!(None, loader.schema_version_of("player", "missing"));
+    }
+
+    #[test]
+    fn test_header_millisecond_timestamp_round_trip() {
+        let header = Header::new(
+            "v1".to_string(),
+            "player".to_string(),
+            "player_join".to_string(),
+        )
+        .with_ttl(chrono::Duration::microseconds(1_500))
+        .with_millisecond_timestamp();
+        assert_eq!(0, header.timestamp().timestamp_subsec_nanos() % 1_000_000);
+
+        let first = serde_json::to_string(&header).unwrap();
+        let parsed: Header = serde_json::from_str(&first).unwrap();
+        assert_eq!(header, parsed);
+        assert_eq!(first, serde_json::to_string(&parsed).unwrap());
+
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct Stamped {
+            #[serde(with = "crate::model::millis_timestamp")]
+            at: chrono::DateTime<chrono::Utc>,
+        }
+
+        let stamped: Stamped =
+            serde_json::from_str(r#"{"at": "2025-01-01T00:00:00.123456789Z"}"#).unwrap();
+        let serialized = serde_json::to_string(&stamped).unwrap();
+        assert_eq!(r#"{"at":"2025-01-01T00:00:00.123Z"}"#, serialized);
+
+        let reparsed: Stamped = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(serialized, serde_json::to_string(&reparsed).unwrap());
+    }
+
+    #[test]
+    fn test_envelope_read_ndjson() {
+        let first = Envelope::new(
+            Header::new(
+                "v1".to_string(),
+                "player".to_string(),
+                "player_join".to_string(),
+            ),
+            json!({"player_uuid": "550e8400-e29b-41d4-a716-446655440000"}),
+        );
+        let second = Envelope::new(
+            Header::new(
+                "v1".to_string(),
+                "player".to_string(),
+                "player_leave".to_string(),
+            ),
+            json!({"reason": "quit"}),
+        );
+
+        let input = format!(
+            "{}\n\n{{not json\n   \n{}\n",
+            serde_json::to_string(&first).unwrap(),
+            serde_json::to_string(&second).unwrap()
+        );
+
+        let results: Vec<_> = Envelope::read_ndjson(input.as_bytes()).collect();
+        assert_eq!(3, results.len());
+        assert_eq!(&first, results[0].as_ref().unwrap());
+        let error = results[1].as_ref().unwrap_err();
+        assert_eq!(3, error.line());
+        assert!(matches!(
+            error,
+            ReadEnvelopeError::InvalidEnvelope { line: 3, .. }
+        ));
+        assert!(error.to_string().starts_with("Invalid envelope on line 3:"));
+        assert!(std::error::Error::source(error).is_some());
+        assert_eq!(&second, results[2].as_ref().unwrap());
+    }
+
+    #[test]
+    fn test_envelope_write_ndjson_round_trip() {
+        let envelopes = vec![
+            Envelope::new(
+                Header::new(
+                    "v1".to_string(),
+                    "player".to_string(),
+                    "player_join".to_string(),
+                ),
+                json!({"player_uuid": "550e8400-e29b-41d4-a716-446655440000"}),
+            ),
+            Envelope::new(
+                Header::new(
+                    "v1".to_string(),
+                    "player".to_string(),
+                    "player_leave".to_string(),
+                ),
+                json!({"reason": "multi\nline"}),
+            ),
+        ];
+
+        let mut buffer = Vec::new();
+        Envelope::write_ndjson(&envelopes, &mut buffer).unwrap();
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert_eq!(2, text.lines().count());
+        assert!(text.ends_with('\n'));
+
+        let read: Vec<Envelope> = Envelope::read_ndjson(buffer.as_slice())
+            .collect::<Result<_, ReadEnvelopeError>>()
+            .unwrap();
+        assert_eq!(envelopes, read);
+
+        struct FailingWriter;
+
+        impl std::io::Write for FailingWriter {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "closed",
+                ))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let error = Envelope::write_ndjson(&envelopes, FailingWriter).unwrap_err();
+        assert_eq!(std::io::ErrorKind::BrokenPipe, error.kind());
+    }
+
+    #[test]
+    fn test_validate_data_property_errors_sorted_by_name() {
+        let validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "zeta": {"type": "string"},
+                "alpha": {"type": "string"},
+                "mid": {"type": "string"}
+            }
+        });
+        let data = json!({"mid": 1, "zeta": 2, "alpha": 3});
+
+        let expected = vec![
+            "Invalid type for field 'alpha'. Expected: string",
+            "Invalid type for field 'mid'. Expected: string",
+            "Invalid type for field 'zeta'. Expected: string",
+        ];
+        assert_eq!(
+            expected,
+            validator.validate_data(&data, &schema).get_errors()
+        );
+
+        let compiled = validator.compile(&schema).unwrap();
+        assert_eq!(
+            expected,
+            validator.validate_compiled(&data, &compiled).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_validate_data_limited() {
+        let validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "required": ["a", "b"],
+            "properties": {
+                "c": {"type": "string"},
+                "d": {
+                    "type": "array",
+                    "items": {"type": "integer"}
+                }
+            }
+        });
+        let data = json!({"c": 1, "d": ["x", "y", "z"]});
+
+        let full = validator.validate_data(&data, &schema);
+        assert_eq!(6, full.get_errors().len());
+        assert!(!full.is_truncated());
+
+        let limited = validator.validate_data_limited(&data, &schema, 3);
+        assert!(!limited.is_valid());
+        assert!(limited.is_truncated());
+        assert_eq!(&full.get_errors()[..3], limited.get_errors());
+
+        let limited = validator.validate_data_limited(&data, &schema, 4);
+        assert_eq!(&full.get_errors()[..4], limited.get_errors());
+
+        let roomy = validator.validate_data_limited(&data, &schema, 10);
+        assert_eq!(full.get_errors(), roomy.get_errors());
+        assert!(!roomy.is_truncated());
+    }
+
+    #[test]
+    fn test_schema_loader_register_schema() {
+        init_test_logging();
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let schema = json!({
+            "type": "object",
+            "properties": {"name": {"type": "string"}}
+        });
+        loader.register_schema("mock", "thing", schema.clone());
+
+        assert_eq!(schema, *loader.load_schema("mock", "thing"));
+        assert_eq!(Some(&schema), loader.load_schema_ref("mock", "thing"));
+        assert!(loader
+            .list_schemas()
+            .contains(&("mock".to_string(), "thing".to_string())));
+        assert_eq!(
+            json!({"type": "string"}),
+            loader
+                .load_schema_subschema("mock", "thing", "/properties/name")
+                .unwrap()
+        );
+
+        loader.register_schema(
+            "mock",
+            "thing",
+            json!({"type": "object", "properties": {"name": {"type": "integer"}}}),
+        );
+        assert_eq!(
+            json!({"type": "integer"}),
+            loader
+                .load_schema_subschema("mock", "thing", "/properties/name")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_schema_loader_from_env() {
+        init_test_logging();
+
+        std::env::set_var("PACTS_SCHEMA_ROOT", "relocated");
+        std::env::set_var("PACTS_DOMAIN", "");
+        std::env::remove_var("PACTS_VERSION");
+
+        let loader = SchemaLoader::from_env();
+        assert_eq!("relocated", loader.get_schema_root());
+        assert_eq!("bees", loader.get_domain());
+        assert_eq!("v1", loader.get_version());
+
+        std::env::remove_var("PACTS_SCHEMA_ROOT");
+    }
+
+    #[test]
+    fn test_schema_version_constant() {
+        assert_eq!(SCHEMA_VERSION, schema_version());
+        assert!(!schema_version().is_empty());
+        assert!(!schema_version().contains('/'));
+    }
+
+    #[test]
+    fn test_schema_load_error_is_std_error() {
+        fn boxed(error: SchemaLoadError) -> Box<dyn std::error::Error + Send + Sync> {
+            Box::new(error)
+        }
+
+        let error = boxed(SchemaLoadError::Source(
+            "No sources defined in sources.yaml".to_string(),
+        ));
+        assert_eq!(
+            "Schema source error: No sources defined in sources.yaml",
+            error.to_string()
+        );
+        assert!(error.source().is_none());
+
+        let json_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let error = boxed(SchemaLoadError::from(json_error));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_service_validate_deprecated_schema_warning() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let schema = json!({
+            "type": "object",
+            "required": ["slot"],
+            "properties": {"slot": {"type": "integer"}}
+        });
+        service.register_schema("legacy", "current_item", schema.clone());
+
+        let mut deprecated_schema = schema;
+        deprecated_schema["deprecated"] = json!(true);
+        service.register_schema("legacy", "old_item", deprecated_schema);
+
+        let current = Envelope::new(
+            Header::new(
+                "v1".to_string(),
+                "legacy".to_string(),
+                "current_item".to_string(),
+            ),
+            json!({"slot": 1}),
+        );
+        let result = service.validate(&current);
+        assert!(result.is_valid());
+        assert!(result.get_warnings().is_empty());
+
+        let old = Envelope::new(
+            Header::new(
+                "v1".to_string(),
+                "legacy".to_string(),
+                "old_item".to_string(),
+            ),
+            json!({"slot": 1}),
+        );
+        let result = service.validate(&old);
+        assert!(result.is_valid(), "{:?}", result.get_errors());
+        assert_eq!(
+            vec!["Schema bees/v1/legacy/old_item is deprecated"],
+            result.get_warnings()
+        );
+
+        let invalid = Envelope::new(old.header().clone(), json!({"slot": "one"}));
+        let result = service.validate(&invalid);
+        assert!(!result.is_valid());
+        assert_eq!(1, result.get_warnings().len());
+    }
+
+    #[test]
+    fn test_service_schema_ref_follows_content_type_schema() {
+        init_test_logging();
+
+        let mut service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let mut schema = json!({"type": "object", "required": ["slot"]});
+        service.register_schema("legacy", "current_item", schema.clone());
+        schema["deprecated"] = json!(true);
+        service.register_schema("legacy", "old_item", schema);
+        service.register_content_type_schema(
+            "application/vnd.legacy+json".to_string(),
+            "legacy".to_string(),
+            "old_item".to_string(),
+        );
+
+        let envelope = Envelope::new(
+            Header::with_content_type(
+                "v1".to_string(),
+                "legacy".to_string(),
+                "current_item".to_string(),
+                "application/vnd.legacy+json".to_string(),
+            ),
+            json!({"slot": 1}),
+        );
+        let result = service.validate(&envelope);
+        assert!(result.is_valid(), "{:?}", result.get_errors());
+        assert_eq!(Some("bees/v1/legacy/old_item"), result.get_schema_ref());
+        assert_eq!(
+            vec!["Schema bees/v1/legacy/old_item is deprecated"],
+            result.get_warnings()
+        );
+
+        service.set_missing_schema_policy(MissingSchemaPolicy::Skip);
+        service.register_content_type_schema(
+            "application/vnd.legacy+json".to_string(),
+            "legacy".to_string(),
+            "missing_item".to_string(),
+        );
+        assert_eq!(
+            Some("bees/v1/legacy/missing_item"),
+            service.validate(&envelope).get_schema_ref()
+        );
+    }
+
+    #[test]
+    fn test_envelope_parse_many_and_validate_many() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.register_schema(
+            "batch",
+            "item",
+            json!({"type": "object", "required": ["slot"], "properties": {"slot": {"type": "integer"}}}),
+        );
+
+        let valid = Envelope::new(
+            Header::new("v1".to_string(), "batch".to_string(), "item".to_string()),
+            json!({"slot": 1}),
+        );
+        let invalid = Envelope::new(valid.header().clone(), json!({}));
+
+        let single = serde_json::to_string(&valid).unwrap();
+        assert_eq!(vec![valid.clone()], Envelope::parse_many(&single).unwrap());
+
+        let array = serde_json::to_string(&vec![valid.clone(), invalid.clone()]).unwrap();
+        assert_eq!(
+            vec![valid.clone(), invalid.clone()],
+            Envelope::parse_many(&array).unwrap()
+        );
+        assert!(Envelope::parse_many("[1]").is_err());
+
+        let results = service.validate_many(&array);
+        assert_eq!(2, results.len());
+        assert!(results[0].is_valid());
+        assert!(!results[1].is_valid());
+
+        let results = service.validate_many(&single);
+        assert_eq!(1, results.len());
+        assert!(results[0].is_valid());
+
+        let mixed = format!("[{}, 42]", single);
+        let results = service.validate_many(&mixed);
+        assert!(results[0].is_valid());
+        assert!(results[1].get_errors()[0].starts_with("Invalid envelope at index 1:"));
+
+        let results = service.validate_many("not json");
+        assert_eq!(1, results.len());
+        assert!(results[0].get_errors()[0].starts_with("Invalid JSON:"));
+    }
+
+    #[test]
+    fn test_schema_loader_load_schema_raw() {
+        use std::io::Write;
+
+        init_test_logging();
+
+        let raw = "{\n  \"type\": \"object\",\n  \"required\": [\"slot\"]\n}\n";
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        archive
+            .start_file(
+                "schemas/bees/v1/inventory/raw_item.json",
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+        archive.write_all(raw.as_bytes()).unwrap();
+        let bytes = archive.finish().unwrap().into_inner();
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        assert!(!loader.is_keep_raw_schemas());
+        assert_eq!(
+            1,
+            loader
+                .load_schemas_from_zip(bytes.clone(), "test.zip")
+                .unwrap()
+        );
+        assert!(matches!(
+            loader.load_schema_raw("inventory", "raw_item"),
+            Err(SchemaLoadError::NotFound(_))
+        ));
+
+        loader.set_keep_raw_schemas(true);
+        assert_eq!(1, loader.load_schemas_from_zip(bytes, "test.zip").unwrap());
+        assert_eq!(
+            raw.as_bytes(),
+            loader.load_schema_raw("inventory", "raw_item").unwrap()
+        );
+        assert_eq!(
+            json!({"type": "object", "required": ["slot"]}),
+            *loader.load_schema("inventory", "raw_item")
+        );
+
+        loader.register_schema("inventory", "raw_item", json!({"type": "object"}));
+        assert!(matches!(
+            loader.load_schema_raw("inventory", "raw_item"),
+            Err(SchemaLoadError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_validator_max_depth() {
+        let mut validator = Validator::standalone();
+        assert_eq!(128, validator.get_max_depth());
+
+        let mut schema = json!({"type": "integer"});
+        let mut data = json!(1);
+        for _ in 0..6 {
+            schema = json!({"type": "array", "items": schema});
+            data = json!([data]);
+        }
+        let bad_data = data.to_string().replace('1', "\"one\"");
+        let bad_data: serde_json::Value = serde_json::from_str(&bad_data).unwrap();
+
+        assert!(validator.validate_data(&data, &schema).is_valid());
+        assert_eq!(
+            vec!["Invalid type at index 0. Expected: integer"],
+            validator.validate_data(&bad_data, &schema).get_errors()
+        );
+
+        validator.set_max_depth(3);
+        let expected = vec!["Maximum nesting depth 3 exceeded"];
+        assert_eq!(
+            expected,
+            validator.validate_data(&data, &schema).get_errors()
+        );
+        assert_eq!(
+            expected,
+            validator.validate_data(&bad_data, &schema).get_errors()
+        );
+
+        let compiled = validator.compile(&schema).unwrap();
+        assert_eq!(
+            expected,
+            validator.validate_compiled(&data, &compiled).get_errors()
+        );
+    }
+
+    #[test]
+    fn test_service_validate_against_any() {
+        init_test_logging();
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.register_schema(
+            "sniff",
+            "numbered",
+            json!({"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}}}),
+        );
+        service.register_schema(
+            "sniff",
+            "named",
+            json!({"type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}}),
+        );
+        let candidates = vec![
+            ("sniff".to_string(), "missing".to_string()),
+            ("sniff".to_string(), "numbered".to_string()),
+            ("sniff".to_string(), "named".to_string()),
+        ];
+
+        let (matched, result) = service.validate_against_any(&json!({"name": "Ada"}), &candidates);
+        assert_eq!(Some(("sniff".to_string(), "named".to_string())), matched);
+        assert!(result.is_valid());
+        assert_eq!(Some("bees/v1/sniff/named"), result.get_schema_ref());
+
+        let (matched, result) = service.validate_against_any(&json!({"other": true}), &candidates);
+        assert_eq!(None, matched);
+        assert_eq!(
+            vec![
+                "sniff/missing: Schema not found: bees/v1/sniff/missing",
+                "sniff/numbered: Required field missing: id",
+                "sniff/named: Required field missing: name",
+            ],
+            result.get_errors()
+        );
+
+        let (matched, result) = service.validate_against_any(&json!({}), &[]);
+        assert_eq!(None, matched);
+        assert!(!result.is_valid());
+    }
+
+    #[test]
+    fn test_validator_inline_refs() {
+        let validator = Validator::standalone();
+        let schema = json!({
+            "$defs": {
+                "slot": {"type": "integer", "description": "Inventory slot"},
+                "node": {
+                    "type": "object",
+                    "properties": {
+                        "slot": {"$ref": "#/$defs/slot"},
+                        "children": {"type": "array", "items": {"$ref": "#/$defs/node"}}
+                    }
+                }
+            },
+            "type": "object",
+            "properties": {
+                "primary": {"$ref": "#/$defs/slot"},
+                "tree": {"$ref": "#/$defs/node"}
+            }
+        });
+
+        let inlined = validator.inline_refs(&schema).unwrap();
+        assert_eq!(
+            json!({
+                "type": "object",
+                "properties": {
+                    "primary": {"type": "integer", "description": "Inventory slot"},
+                    "tree": {
+                        "type": "object",
+                        "properties": {
+                            "slot": {"type": "integer", "description": "Inventory slot"},
+                            "children": {
+                                "type": "array",
+                                "items": {"$circularRef": "#/$defs/node"}
+                            }
+                        }
+                    }
+                }
+            }),
+            inlined
+        );
+
+        let broken = json!({"properties": {"slot": {"$ref": "#/$defs/missing"}}});
+        assert_eq!(
+            Err("Unresolved $ref: #/$defs/missing".to_string()),
+            validator.inline_refs(&broken)
+        );
+    }
+
+    #[test]
+    fn test_validator_case_insensitive_keys() {
+        let mut validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "id"],
+            "properties": {
+                "name": {"type": "string"},
+                "id": {"type": "integer"}
+            }
+        });
+        let data = json!({"Name": "Ada", "ID": "seven"});
+
+        assert_eq!(
+            vec!["Required field missing: name", "Required field missing: id"],
+            validator.validate_data(&data, &schema).get_errors()
+        );
+
+        validator.set_case_insensitive_keys(true);
+        assert!(validator.is_case_insensitive_keys());
+        let expected = vec!["Invalid type for field 'id'. Expected: integer"];
+        assert_eq!(
+            expected,
+            validator.validate_data(&data, &schema).get_errors()
+        );
+
+        let compiled = validator.compile(&schema).unwrap();
+        assert_eq!(
+            expected,
+            validator.validate_compiled(&data, &compiled).get_errors()
+        );
+
+        assert!(validator
+            .validate_data(&json!({"name": "Ada", "id": 7}), &schema)
+            .is_valid());
+    }
+
+    #[test]
+    fn test_schema_loader_extends() {
+        init_test_logging();
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        loader.register_schema(
+            "common",
+            "base_entity",
+            json!({
+                "type": "object",
+                "required": ["id", "created_at"],
+                "properties": {
+                    "id": {"type": "string"},
+                    "created_at": {"type": "string"}
+                }
+            }),
+        );
+        loader.register_schema(
+            "player",
+            "profile",
+            json!({
+                "extends": "common/base_entity",
+                "type": "object",
+                "required": ["name", "id"],
+                "properties": {
+                    "id": {"type": "integer"},
+                    "name": {"type": "string"}
+                }
+            }),
+        );
+
+        let schema = loader.load_schema("player", "profile");
+        assert_eq!(None, schema.get("extends"));
+        assert_eq!(json!(["id", "created_at", "name"]), schema["required"]);
+        assert_eq!(json!({"type": "integer"}), schema["properties"]["id"]);
+        assert_eq!(
+            json!({"type": "string"}),
+            schema["properties"]["created_at"]
+        );
+
+        let validator = Validator::standalone();
+        assert_eq!(
+            vec![
+                "Required field missing: created_at",
+                "Invalid type for field 'id'. Expected: integer",
+            ],
+            validator
+                .validate_data(&json!({"id": "abc", "name": "Ada"}), &schema)
+                .get_errors()
+        );
+
+        loader.register_schema("loop", "a", json!({"extends": "loop/b"}));
+        loader.register_schema("loop", "b", json!({"extends": "loop/a"}));
+        assert!(matches!(
+            loader.load_schema_version("v1", "loop", "a"),
+            Err(SchemaLoadError::InvalidSchema(_))
+        ));
+
+        loader.register_schema("orphan", "child", json!({"extends": "common/missing"}));
+        assert!(matches!(
+            loader.load_schema_version("v1", "orphan", "child"),
+            Err(SchemaLoadError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_data_partial() {
+        let validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "slots"],
+            "properties": {
+                "name": {"type": "string", "maxLength": 8},
+                "rank": {"enum": ["bronze", "silver"]},
+                "slots": {
+                    "type": "array",
+                    "items": {"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}}}
+                }
+            }
+        });
+
+        let patch = json!({"rank": "silver", "slots": [{}]});
+        assert_eq!(
+            vec!["Required field missing: name", "Required field missing: id"],
+            validator.validate_data(&patch, &schema).get_errors()
+        );
+        assert!(validator.validate_data_partial(&patch, &schema).is_valid());
+
+        let bad_patch = json!({"rank": "gold", "slots": [{"id": "one"}]});
+        let result = validator.validate_data_partial(&bad_patch, &schema);
+        assert_eq!(2, result.get_errors().len());
+        assert!(result
+            .get_errors()
+            .iter()
+            .all(|e| !e.starts_with("Required")));
+    }
+
+    #[test]
+    fn test_validator_validate_and_coerce() {
+        let mut validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "amount": {"type": "integer"},
+                "weight": {"type": "number"},
+                "stackable": {"type": "boolean"},
+                "label": {"type": "string", "default": "none"},
+                "levels": {"type": "array", "items": {"type": "integer"}}
+            }
+        });
+        let original = json!({
+            "amount": "42",
+            "weight": "1.5",
+            "stackable": "true",
+            "levels": ["1", "two"]
+        });
+
+        let mut data = original.clone();
+        let result = validator.validate_and_coerce(&mut data, &schema);
+        assert_eq!(original, data);
+        assert_eq!(5, result.get_errors().len());
+
+        validator.set_coerce_types(true);
+        let result = validator.validate_and_coerce(&mut data, &schema);
+        assert_eq!(
+            json!({"amount": 42, "weight": 1.5, "stackable": true, "levels": [1, "two"]}),
+            data
+        );
+        assert_eq!(
+            vec!["Invalid type for field 'levels' at index 1. Expected: integer"],
+            result.get_errors()
+        );
+
+        assert_eq!(
+            json!({"amount": 42, "weight": 1.5, "stackable": true, "label": "none", "levels": [1, "two"]}),
+            validator.normalize(&original, &schema)
+        );
+    }
+
+    #[test]
+    fn test_envelope_map_data() {
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("source".to_string(), json!("ingest"));
+        let envelope = Envelope::with_metadata(
+            Header::new(
+                "v1".to_string(),
+                "inventory".to_string(),
+                "inventory_item".to_string(),
+            ),
+            json!({"amount": 2, "price": 3}),
+            metadata,
+        );
+
+        let mapped = envelope.clone().map_data(|mut data| {
+            let total = data["amount"].as_i64().unwrap() * data["price"].as_i64().unwrap();
+            data["total"] = json!(total);
+            data
+        });
+        assert_eq!(json!({"amount": 2, "price": 3, "total": 6}), *mapped.data());
+        assert_eq!(envelope.header(), mapped.header());
+        assert_eq!(envelope.metadata(), mapped.metadata());
+
+        let failed: Result<Envelope, String> = envelope
+            .clone()
+            .try_map_data(|_| Err("no total".to_string()));
+        assert_eq!(Err("no total".to_string()), failed);
+
+        let replaced = envelope
+            .try_map_data::<String, _>(|_| Ok(json!([1, 2])))
+            .unwrap();
+        assert_eq!(json!([1, 2]), *replaced.data());
+    }
+
+    #[test]
+    fn test_validator_reject_null_required() {
+        let mut validator = Validator::standalone();
+        let schema = json!({"type": "object", "required": ["name", "id"]});
+        let data = json!({"name": null});
+
+        assert_eq!(
+            vec!["Required field missing: id"],
+            validator.validate_data(&data, &schema).get_errors()
+        );
+
+        validator.set_reject_null_required(true);
+        let expected = vec!["Required field is null: name", "Required field missing: id"];
+        assert_eq!(
+            expected,
+            validator.validate_data(&data, &schema).get_errors()
+        );
+
+        let compiled = validator.compile(&schema).unwrap();
+        assert_eq!(
+            expected,
+            validator.validate_compiled(&data, &compiled).get_errors()
+        );
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn test_schema_loader_from_archive_reads_nested_tar_gz() {
+        init_test_logging();
+
+        let path =
+            std::env::temp_dir().join(format!("pacts-archive-{}.tar.gz", std::process::id()));
+        {
+            let file = std::fs::File::create(&path).unwrap();
+            let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            let mut builder = tar::Builder::new(encoder);
+            let entries = [
+                (
+                    "release/bees/v1/player/join.json",
+                    json!({"type": "object", "required": ["player_id"]}),
+                ),
+                (
+                    "release/bees/v1/world/deep/ignored.txt",
+                    json!("not a schema"),
+                ),
+                (
+                    "release/bees/v1/inventory/update.json",
+                    json!({"type": "object"}),
+                ),
+            ];
+            for (name, value) in entries {
+                let body = serde_json::to_vec(&value).unwrap();
+                let mut header = tar::Header::new_gnu();
+                header.set_size(body.len() as u64);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, name, body.as_slice())
+                    .unwrap();
+            }
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+
+        let result = SchemaLoader::from_archive(&path);
+        std::fs::remove_file(&path).unwrap();
+        let mut loader = result.unwrap();
+
+        assert_eq!(loader.get_domain(), "bees");
+        assert_eq!(loader.get_version(), "v1");
+        assert_eq!(
+            loader.list_schemas(),
+            vec![
+                ("inventory".to_string(), "update".to_string()),
+                ("player".to_string(), "join".to_string()),
+            ]
+        );
+        assert_eq!(
+            loader.load_schema("player", "join")["required"],
+            json!(["player_id"])
+        );
+
+        let missing = std::env::temp_dir().join("pacts-archive-missing.tar.gz");
+        assert!(SchemaLoader::from_archive(&missing).is_err());
+    }
+
+    #[test]
+    fn test_typed_envelope_round_trip() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct PlayerJoin {
+            player_id: String,
+            level: u32,
+        }
+
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("source".to_string(), json!("lobby"));
+        let envelope = Envelope::with_metadata(
+            Header::new("v1".to_string(), "player".to_string(), "join".to_string()),
+            json!({"player_id": "p-1", "level": 7}),
+            metadata,
+        );
+
+        let typed = TypedEnvelope::<PlayerJoin>::from_envelope(envelope.clone()).unwrap();
+        assert_eq!(
+            typed.data(),
+            &PlayerJoin {
+                player_id: "p-1".to_string(),
+                level: 7
+            }
+        );
+        assert_eq!(typed.header(), envelope.header());
+        assert_eq!(typed.into_envelope().unwrap(), envelope);
+
+        let wrong_shape = Envelope::new(
+            Header::new("v1".to_string(), "player".to_string(), "join".to_string()),
+            json!({"player_id": 5}),
+        );
+        assert!(TypedEnvelope::<PlayerJoin>::from_envelope(wrong_shape).is_err());
+    }
+
+    #[test]
+    fn test_validate_data_grouped_by_field() {
+        init_test_logging();
+        let validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "age"],
+            "properties": {
+                "age": {"type": "integer"},
+                "role": {"enum": ["admin", "user"]},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        });
+
+        let grouped = validator.validate_data_grouped(
+            &json!({"age": "old", "role": "root", "tags": ["a", 1]}),
+            &schema,
+        );
+        assert_eq!(grouped["/name"], vec!["Required field missing".to_string()]);
+        assert_eq!(
+            grouped["/age"],
+            vec!["Invalid type. Expected: integer".to_string()]
+        );
+        assert_eq!(
+            grouped["/role"],
+            vec![r#"Invalid value. Expected one of: ["admin","user"]"#.to_string()]
+        );
+        assert_eq!(
+            grouped["/tags/1"],
+            vec!["Invalid type. Expected: string".to_string()]
+        );
+        assert!(!grouped.contains_key(""));
+
+        let nested = json!({
+            "type": "object",
+            "properties": {
+                "owner": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {"name": {"type": "string"}}
+                },
+                "hives": {
+                    "type": "array",
+                    "items": {"type": "object", "properties": {"name": {"type": "string"}}}
+                }
+            }
+        });
+        let grouped = validator.validate_data_grouped(
+            &json!({"owner": {}, "hives": [{"name": 1}, {"name": "ok"}, {"name": 2}]}),
+            &nested,
+        );
+        let mut keys: Vec<&String> = grouped.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["/hives/0/name", "/hives/2/name", "/owner/name"]);
+        assert_eq!(
+            grouped["/hives/2/name"],
+            vec!["Invalid type. Expected: string".to_string()]
+        );
+
+        let grouped = validator.validate_data_grouped(&json!("not an object"), &schema);
+        assert_eq!(grouped.keys().collect::<Vec<_>>(), vec![""]);
+
+        assert!(validator
+            .validate_data_grouped(&json!({"name": "x", "age": 3}), &schema)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_create_envelope_with_defaults() {
+        init_test_logging();
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.register_schema(
+            "test",
+            "defaults",
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "level": {"type": "integer", "default": 1},
+                    "settings": {
+                        "type": "object",
+                        "default": {"sound": true}
+                    }
+                }
+            }),
+        );
+
+        let envelope = service
+            .create_envelope_with_defaults(
+                "test".to_string(),
+                "defaults".to_string(),
+                json!({"name": "bee", "level": 4}),
+            )
+            .unwrap();
+        assert_eq!(
+            envelope.data(),
+            &json!({"name": "bee", "level": 4, "settings": {"sound": true}})
+        );
+        assert_eq!(envelope.header().schema_name(), "defaults");
+
+        let missing = service.create_envelope_with_defaults(
+            "test".to_string(),
+            "nonexistent".to_string(),
+            json!({}),
+        );
+        assert!(matches!(missing, Err(SchemaLoadError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_validator_custom_rule() {
+        init_test_logging();
+        let mut validator = Validator::standalone();
+        validator.register_custom_rule(
+            "date_order",
+            Box::new(|value| {
+                let start = value.get("start_date").and_then(|d| d.as_str());
+                let end = value.get("end_date").and_then(|d| d.as_str());
+                match (start, end) {
+                    (Some(start), Some(end)) if start >= end => Err(format!(
+                        "start_date {} must be before end_date {}",
+                        start, end
+                    )),
+                    _ => Ok(()),
+                }
+            }),
+        );
+
+        let schema = json!({
+            "type": "object",
+            "x-custom": "date_order",
+            "properties": {
+                "stages": {
+                    "type": "array",
+                    "items": {"type": "object", "x-custom": "date_order"}
+                },
+                "code": {"type": "string", "x-custom": "unregistered"}
+            }
+        });
+        let data = json!({
+            "start_date": "2025-03-01",
+            "end_date": "2025-01-01",
+            "stages": [
+                {"start_date": "2025-01-01", "end_date": "2025-02-01"},
+                {"start_date": "2025-02-01", "end_date": "2025-02-01"}
+            ],
+            "code": "A1"
+        });
+
+        let expected = vec![
+            "Unknown custom rule: unregistered".to_string(),
+            "start_date 2025-02-01 must be before end_date 2025-02-01".to_string(),
+            "start_date 2025-03-01 must be before end_date 2025-01-01".to_string(),
+        ];
+        let result = validator.validate_data(&data, &schema);
+        assert_eq!(result.get_errors(), expected.as_slice());
+        let compiled = validator.compile(&schema).unwrap();
+        assert_eq!(
+            validator.validate_compiled(&data, &compiled).get_errors(),
+            expected.as_slice()
+        );
+
+        let valid = json!({"start_date": "2025-01-01", "end_date": "2025-03-01"});
+        assert!(validator
+            .validate_data(&valid, &json!({"x-custom": "date_order"}))
+            .is_valid());
+        assert!(!validator
+            .validate_schema(&json!({"x-custom": 1}))
+            .is_valid());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_validate_batch_parallel_keeps_input_order() {
+        init_test_logging();
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.register_schema(
+            "test",
+            "counter",
+            json!({
+                "type": "object",
+                "required": ["count"],
+                "properties": {"count": {"type": "integer"}}
+            }),
+        );
+
+        let envelopes: Vec<Envelope> = (0..200)
+            .map(|i| {
+                let data = if i % 3 == 0 {
+                    json!({"count": format!("{}", i)})
+                } else {
+                    json!({"count": i})
+                };
+                service.create_envelope("test".to_string(), "counter".to_string(), data)
+            })
+            .collect();
+
+        let sequential: Vec<ValidationResult> = envelopes
+            .iter()
+            .map(|envelope| service.validate(envelope))
+            .collect();
+        let parallel = service.validate_batch_parallel(&envelopes);
+
+        assert_eq!(parallel.len(), envelopes.len());
+        for (index, (parallel, sequential)) in parallel.iter().zip(&sequential).enumerate() {
+            assert_eq!(parallel.is_valid(), index % 3 != 0, "envelope {}", index);
+            assert_eq!(parallel.get_errors(), sequential.get_errors());
+            assert_eq!(parallel.get_schema_ref(), sequential.get_schema_ref());
+        }
+    }
+
+    #[test]
+    fn test_schema_loader_dump_and_load_cache() {
+        init_test_logging();
+        let path = std::env::temp_dir().join(format!("pacts-cache-{}.json", std::process::id()));
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let schema = json!({"type": "object", "required": ["id"]});
+        loader.register_schema("cached", "entry", schema.clone());
+        let cached = loader.list_all_schemas().len();
+        loader.dump_cache(&path).unwrap();
+
+        loader.clear_cache();
+        assert_eq!(loader.load_cache(&path).unwrap(), cached);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loader.list_all_schemas().len(), cached);
+        assert_eq!(*loader.load_schema("cached", "entry"), schema);
+        assert!(matches!(
+            loader.load_cache(&path),
+            Err(SchemaLoadError::Io(_))
+        ));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_validation_tracing_spans_and_events() {
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata};
+
+        #[derive(Clone, Default)]
+        struct Recorder {
+            spans: Arc<Mutex<Vec<String>>>,
+            failures: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+                let mut spans = self.spans.lock().unwrap();
+                spans.push(attributes.metadata().name().to_string());
+                Id::from_u64(spans.len() as u64)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                if *event.metadata().level() == tracing::Level::DEBUG {
+                    self.failures
+                        .lock()
+                        .unwrap()
+                        .push(event.metadata().name().to_string());
+                }
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let recorder = Recorder::default();
+        let validator = Validator::standalone();
+        let schema = json!({"type": "object", "required": ["id"]});
+        tracing::subscriber::with_default(recorder.clone(), || {
+            assert!(validator
+                .validate_data(&json!({"id": 1}), &schema)
+                .is_valid());
+            assert!(!validator.validate_data(&json!({}), &schema).is_valid());
+        });
+
+        assert_eq!(
+            *recorder.spans.lock().unwrap(),
+            vec!["validate_data", "validate_data"]
+        );
+        assert_eq!(recorder.failures.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_validate_data_with_schema_file() {
+        init_test_logging();
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        let dir = std::env::temp_dir();
+        let schema_path = dir.join(format!("pacts-adhoc-schema-{}.json", std::process::id()));
+        let broken_path = dir.join(format!("pacts-adhoc-broken-{}.json", std::process::id()));
+        std::fs::write(&schema_path, r#"{"type": "object", "required": ["id"]}"#).unwrap();
+        std::fs::write(&broken_path, "{ not json").unwrap();
+
+        let valid = service.validate_data_with_schema_file(&json!({"id": 1}), &schema_path);
+        let invalid = service.validate_data_with_schema_file(&json!({}), &schema_path);
+        let broken = service.validate_data_with_schema_file(&json!({}), &broken_path);
+        let missing = service
+            .validate_data_with_schema_file(&json!({}), &dir.join("pacts-no-such-schema.json"));
+        std::fs::remove_file(&schema_path).unwrap();
+        std::fs::remove_file(&broken_path).unwrap();
+
+        assert!(valid.is_valid());
+        assert_eq!(valid.get_schema_source(), Some("file"));
+        assert_eq!(invalid.get_errors(), ["Required field missing: id"]);
+        assert!(broken.get_errors()[0].contains("is not valid JSON"));
+        assert!(missing.get_errors()[0].contains("is unreadable"));
+    }
+
+    #[test]
+    fn test_validator_context_read_only_write_only() {
+        init_test_logging();
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string", "readOnly": true},
+                "password": {"type": "string", "writeOnly": true},
+                "name": {"type": "string"}
+            }
+        });
+        let data = json!({"id": "u-1", "password": "hunter2", "name": "bee"});
+        let mut validator = Validator::standalone();
+        assert_eq!(validator.get_context(), ValidationContext::Neutral);
+        assert!(validator.validate_data(&data, &schema).is_valid());
+
+        let compiled = validator.compile(&schema).unwrap();
+        for (context, expected) in [
+            (ValidationContext::Write, "Read-only field present: id"),
+            (
+                ValidationContext::Read,
+                "Write-only field present: password",
+            ),
+        ] {
+            validator.set_context(context);
+            assert_eq!(
+                validator.validate_data(&data, &schema).get_errors(),
+                [expected]
+            );
+            assert_eq!(
+                validator.validate_compiled(&data, &compiled).get_errors(),
+                [expected]
+            );
+        }
+
+        validator.set_context(ValidationContext::Write);
+        assert!(validator
+            .validate_data(&json!({"password": "hunter2", "name": "bee"}), &schema)
+            .is_valid());
+    }
+
+    #[test]
+    fn test_envelope_schema_ref_drives_validation() {
+        init_test_logging();
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.register_schema(
+            "test",
+            "versioned",
+            json!({"type": "object", "required": ["id"]}),
+        );
+
+        let envelope = service.create_envelope(
+            "test".to_string(),
+            "versioned".to_string(),
+            json!({"id": 1}),
+        );
+        assert_eq!(
+            envelope.schema_ref(),
+            (
+                "v1".to_string(),
+                "test".to_string(),
+                "versioned".to_string()
+            )
+        );
+        let result = service.validate(&envelope);
+        assert!(result.is_valid());
+        assert_eq!(result.get_schema_ref(), Some("bees/v1/test/versioned"));
+
+        let mismatched = Envelope::new(
+            Header::new(
+                "v7".to_string(),
+                "test".to_string(),
+                "versioned".to_string(),
+            ),
+            json!({"id": 1}),
+        );
+        let result = service.validate(&mismatched);
+        assert!(!result.is_valid());
+        assert_eq!(result.get_schema_ref(), Some("bees/v7/test/versioned"));
+        assert!(result.get_errors()[0].starts_with("Schema not found"));
+    }
+
+    #[test]
+    fn test_validator_resolves_chained_defs_refs() {
+        init_test_logging();
+        let validator = Validator::standalone();
+        let schema = json!({
+            "$defs": {
+                "item": {
+                    "type": "object",
+                    "required": ["material"],
+                    "properties": {"material": {"$ref": "#/$defs/material"}}
+                },
+                "material": {"type": "string", "enum": ["Paper", "Stone"]}
+            },
+            "definitions": {
+                "slot": {"type": "integer"}
+            },
+            "type": "object",
+            "properties": {
+                "item": {"$ref": "#/$defs/item"},
+                "items": {"type": "array", "items": {"$ref": "#/$defs/item"}},
+                "slot": {"$ref": "#/definitions/slot"}
+            }
+        });
+        let compiled = validator.compile(&schema).unwrap();
+
+        let valid =
+            json!({"item": {"material": "Paper"}, "items": [{"material": "Stone"}], "slot": 1});
+        assert!(validator.validate_data(&valid, &schema).is_valid());
+        assert!(validator.validate_compiled(&valid, &compiled).is_valid());
+
+        let invalid = json!({"items": [{"material": "Wood"}, {}], "slot": "one"});
+        let expected = vec![
+            r#"Invalid value for field 'material'. Expected one of: ["Paper","Stone"]"#.to_string(),
+            "Required field missing: material".to_string(),
+            "Invalid type for field 'slot'. Expected: integer".to_string(),
+        ];
+        assert_eq!(
+            validator.validate_data(&invalid, &schema).get_errors(),
+            expected.as_slice()
+        );
+        assert_eq!(
+            validator
+                .validate_compiled(&invalid, &compiled)
+                .get_errors(),
+            expected.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_schema_loader_rejects_oversized_and_deep_schemas() {
+        use std::io::Write;
+
+        init_test_logging();
+
+        let deep = format!("{}{}", "[".repeat(20), "]".repeat(20));
+        let large = format!(r#"{{"description": "{}"}}"#, "x".repeat(2048));
+        let nested_string = r#"{"pattern": "[[[[[[[[[[[[[[[[[[[[", "type": "string"}"#;
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, body) in [
+            ("schemas/bees/v1/limits/deep.json", deep.as_str()),
+            ("schemas/bees/v1/limits/large.json", large.as_str()),
+            ("schemas/bees/v1/limits/ok.json", nested_string),
+        ] {
+            archive
+                .start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            archive.write_all(body.as_bytes()).unwrap();
+        }
+        let bytes = archive.finish().unwrap().into_inner();
+
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        assert_eq!(loader.get_max_schema_size(), 8 * 1024 * 1024);
+        assert_eq!(loader.get_max_schema_depth(), 64);
+        loader.set_max_schema_size(1024);
+        loader.set_max_schema_depth(10);
+
+        assert_eq!(
+            1,
+            loader.load_schemas_from_zip(bytes, "limits.zip").unwrap()
+        );
+        assert!(loader.load_schema_ref("limits", "ok").is_some());
+        let errors = loader.get_load_errors();
+        assert!(errors
+            .contains(&"Schema schemas/bees/v1/limits/deep.json exceeds max depth 10".to_string()));
+        assert!(errors.contains(
+            &"Schema schemas/bees/v1/limits/large.json exceeds max size 1KiB".to_string()
+        ));
+    }
+
+    #[test]
+    fn test_header_omits_absent_optional_fields() {
+        let header = Header::new("v1".to_string(), "test".to_string(), "test".to_string());
+        let serialized = serde_json::to_value(&header).unwrap();
+        assert!(serialized.get("content_type").is_none());
+        assert!(serialized.get("expires_at").is_none());
+
+        let with_type = Header::with_content_type(
+            "v1".to_string(),
+            "test".to_string(),
+            "test".to_string(),
+            "application/json".to_string(),
+        );
+        assert_eq!(
+            serde_json::to_value(&with_type).unwrap()["content_type"],
+            json!("application/json")
+        );
+
+        let omitted: Header = serde_json::from_value(json!({
+            "schema_version": "v1",
+            "schema_category": "test",
+            "schema_name": "test",
+            "timestamp": "2025-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        let explicit_null: Header = serde_json::from_value(json!({
+            "schema_version": "v1",
+            "schema_category": "test",
+            "schema_name": "test",
+            "timestamp": "2025-01-01T00:00:00Z",
+            "content_type": null,
+            "expires_at": null
+        }))
+        .unwrap();
+        assert_eq!(omitted, explicit_null);
+        assert!(omitted.content_type().is_none());
+    }
+
+    #[test]
+    fn test_envelope_without_metadata_omits_key() {
+        let envelope = Envelope::new(
+            Header::new("v1".to_string(), "test".to_string(), "test".to_string()),
+            json!({"id": 1}),
+        );
+        let serialized = serde_json::to_string(&envelope).unwrap();
+        assert!(!serialized.contains("\"metadata\""), "{}", serialized);
+
+        let parsed: Envelope = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(parsed, envelope);
+
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("trace_id".to_string(), json!("t-1"));
+        let with_metadata = Envelope::with_metadata(envelope.header.clone(), json!({}), metadata);
+        assert!(serde_json::to_string(&with_metadata)
+            .unwrap()
+            .contains("\"metadata\""));
+    }
+
+    #[test]
+    fn test_validator_is_sync_and_shares_loader_with_service() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Validator>();
+
+        init_test_logging();
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.schema_loader().lock().unwrap().register_schema(
+            "test",
+            "shared",
+            json!({"type": "object", "required": ["id"]}),
+        );
+
+        let envelope = service.create_envelope("test".to_string(), "shared".to_string(), json!({}));
+        let validator = std::sync::Arc::clone(service.validator());
+        let result = std::thread::spawn(move || validator.validate(&envelope))
+            .join()
+            .unwrap();
+        assert_eq!(result.get_errors(), ["Required field missing: id"]);
+    }
+
+    #[test]
+    fn test_validate_and_canonicalize() {
+        init_test_logging();
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.register_schema(
+            "test",
+            "canonical",
+            json!({"type": "object", "required": ["id"]}),
+        );
+
+        let header: Header = serde_json::from_value(json!({
+            "schema_version": "v1",
+            "schema_category": "test",
+            "schema_name": "canonical",
+            "timestamp": "2025-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        let envelope = Envelope::new(header.clone(), json!({"zeta": [{"b": 1, "a": 2}], "id": 7}));
+
+        let canonical = service.validate_and_canonicalize(&envelope).unwrap();
+        assert_eq!(
+            canonical,
+            r#"{"data":{"id":7,"zeta":[{"a":2,"b":1}]},"header":{"schema_category":"test","schema_name":"canonical","schema_version":"v1","timestamp":"2025-01-01T00:00:00Z"}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Envelope>(&canonical).unwrap(),
+            envelope
+        );
+
+        let invalid = Envelope::new(header, json!({}));
+        match service.validate_and_canonicalize(&invalid) {
+            Err(PactsError::Validation(result)) => {
+                assert_eq!(result.get_errors(), ["Required field missing: id"])
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validator_validate_stream() {
+        init_test_logging();
+        let validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {"id": {"type": "integer"}, "tags": {"type": "array"}}
+        });
+
+        let document = r#" [
+            {"id": 1, "note": "brackets ] and , in \"strings\" [", "tags": [1, [2]]},
+            {"id": "two"},
+            {"id": 3,,},
+            {"tags": []}
+        ] trailing"#;
+        let results: Vec<(usize, ValidationResult)> = validator
+            .validate_stream(document.as_bytes(), &schema)
+            .collect();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+        assert!(results[0].1.is_valid());
+        assert_eq!(
+            results[1].1.get_errors(),
+            ["Invalid type for field 'id'. Expected: integer"]
+        );
+        assert!(results[2].1.get_errors()[0].starts_with("Invalid JSON:"));
+        assert_eq!(results[3].1.get_errors(), ["Required field missing: id"]);
+
+        assert_eq!(
+            validator
+                .validate_stream(" [ ] ".as_bytes(), &schema)
+                .count(),
+            0
+        );
+
+        let not_array: Vec<_> = validator
+            .validate_stream(r#"{"id": 1}"#.as_bytes(), &schema)
+            .collect();
+        assert_eq!(not_array.len(), 1);
+        assert_eq!(
+            not_array[0].1.get_errors(),
+            ["Expected a top-level JSON array"]
+        );
+
+        let truncated: Vec<_> = validator
+            .validate_stream(r#"[{"id": 1}, {"id""#.as_bytes(), &schema)
+            .collect();
+        assert_eq!(truncated.len(), 2);
+        assert!(truncated[0].1.is_valid());
+        assert_eq!(truncated[1].1.get_errors(), ["Unexpected end of array"]);
+    }
+
+    #[test]
+    fn test_validator_is_backward_compatible() {
+        init_test_logging();
+        let old = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "integer"},
+                "status": {"type": "string", "enum": ["open", "closed", "archived"]},
+                "score": {"type": ["integer", "string"]},
+                "legacy": {"type": "string"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        });
+
+        let compatible = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "number"},
+                "status": {"type": "string", "enum": ["open", "closed", "archived", "draft"]},
+                "score": {"type": ["integer", "string", "null"]},
+                "legacy": {"type": "string"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "note": {"type": "string"}
+            }
+        });
+        assert!(Validator::is_backward_compatible(&old, &compatible).is_empty());
+
+        let breaking = json!({
+            "type": "object",
+            "required": ["id", "owner"],
+            "properties": {
+                "id": {"type": "integer"},
+                "status": {"type": "string", "enum": ["open", "closed"]},
+                "score": {"type": "integer"},
+                "owner": {"type": "string"},
+                "tags": {"type": "array", "items": {"type": "string", "enum": ["a", "b"]}}
+            }
+        });
+        let incompatibilities = Validator::is_backward_compatible(&old, &breaking);
+        let found: Vec<(&str, &str)> = incompatibilities
+            .iter()
+            .map(|i| (i.path.as_str(), i.description.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("/required", "New required field: owner"),
+                ("/properties/legacy", "Property removed: legacy"),
+                (
+                    "/properties/score/type",
+                    "Type narrowed from integer, string to integer"
+                ),
+                (
+                    "/properties/status/enum",
+                    "Enum value removed: \"archived\""
+                ),
+                ("/properties/tags/items/enum", "Enum added"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_value_of() {
+        init_test_logging();
+        #[derive(serde::Serialize)]
+        struct Item {
+            name: String,
+            quantity: i64,
+        }
+
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "quantity"],
+            "properties": {"name": {"type": "string"}, "quantity": {"type": "string"}}
+        });
+        let item = Item {
+            name: "honey".to_string(),
+            quantity: 3,
+        };
+        let mut unserializable = std::collections::HashMap::new();
+        unserializable.insert(vec![1u8], 1u8);
+
+        let validator = Validator::standalone();
+        assert_eq!(
+            validator.validate_value_of(&item, &schema).get_errors(),
+            ["Invalid type for field 'quantity'. Expected: string"]
+        );
+        let failure = validator.validate_value_of(&unserializable, &schema);
+        assert!(!failure.is_valid());
+        assert!(failure.get_errors()[0].starts_with("Failed to serialize value:"));
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.register_schema("inventory", "item", schema);
+        let result = service.validate_value_of(&item, "inventory", "item");
+        assert_eq!(
+            result.get_errors(),
+            ["Invalid type for field 'quantity'. Expected: string"]
+        );
+        assert_eq!(result.get_schema_ref(), Some("bees/v1/inventory/item"));
+        let failure = service.validate_value_of(&unserializable, "inventory", "item");
+        assert!(failure.get_errors()[0].starts_with("Failed to serialize value:"));
+    }
+
+    #[test]
+    fn test_schema_loader_default_schema() {
+        init_test_logging();
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        assert!(loader.get_default_schema().is_none());
+        assert!(matches!(
+            loader.load_schema_version("v1", "events", "unknown"),
+            Err(SchemaLoadError::NotFound(_))
+        ));
+
+        let base = json!({"type": "object", "required": ["event_id"]});
+        loader.set_default_schema(base.clone());
+        assert_eq!(loader.get_default_schema(), Some(&base));
+        assert_eq!(*loader.load_schema("events", "unknown"), base);
+
+        let specific = json!({"type": "object", "required": ["event_id", "player"]});
+        loader.register_schema("events", "player_join", specific.clone());
+        assert_eq!(*loader.load_schema("events", "player_join"), specific);
+
+        let broken = json!({"type": "object", "extends": "events/missing_base"});
+        loader.register_schema("events", "broken", broken);
+        assert!(matches!(
+            loader.load_schema_version("v1", "events", "broken"),
+            Err(SchemaLoadError::NotFound(key)) if key == "bees/v1/events/missing_base"
+        ));
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service
+            .schema_loader()
+            .lock()
+            .unwrap()
+            .set_default_schema(base);
+        let envelope = Envelope::new(
+            Header::new(
+                "v1".to_string(),
+                "events".to_string(),
+                "unknown".to_string(),
+            ),
+            json!({"kind": "custom"}),
+        );
+        assert_eq!(
+            service.validate(&envelope).get_errors(),
+            ["Required field missing: event_id"]
+        );
+    }
+
+    #[test]
+    fn test_validator_validate_at_pointer() {
+        init_test_logging();
+        let validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "required": ["item"],
+            "properties": {"item": {"type": "string"}}
+        });
+        let data = json!({
+            "envelope": {"id": 7},
+            "payload": {"item": "honey"},
+            "batch": [{"item": 3}]
+        });
+
+        assert!(validator
+            .validate_at_pointer(&data, "/payload", &schema)
+            .is_valid());
+        assert!(!validator.validate_data(&data, &schema).is_valid());
+        assert_eq!(
+            validator
+                .validate_at_pointer(&data, "/batch/0", &schema)
+                .get_errors(),
+            ["Invalid type for field 'item'. Expected: string"]
+        );
+        assert_eq!(
+            validator
+                .validate_at_pointer(&data, "/missing", &schema)
+                .get_errors(),
+            ["No value at pointer /missing"]
+        );
+        assert!(validator
+            .validate_at_pointer(&json!({"item": "wax"}), "", &schema)
+            .is_valid());
+    }
+
+    #[test]
+    fn test_envelope_from_header_and_parts() {
+        let header = Header::new(
+            "v1".to_string(),
+            "player".to_string(),
+            "player_join".to_string(),
+        );
+
+        let minimal = Envelope::from(header.clone());
+        assert_eq!(minimal.header(), &header);
+        assert!(minimal.data().is_null());
+        assert!(minimal.metadata().is_none());
+        let converted: Envelope = header.clone().into();
+        assert_eq!(converted, minimal);
+
+        let data = json!({"player_uuid": "550e8400-e29b-41d4-a716-446655440000"});
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("source".to_string(), json!("lobby"));
+        assert_eq!(
+            Envelope::from_parts(header.clone(), data.clone(), Some(metadata.clone())),
+            Envelope::with_metadata(header.clone(), data.clone(), metadata)
+        );
+        assert_eq!(
+            Envelope::from_parts(header.clone(), data.clone(), None),
+            Envelope::new(header, data)
+        );
+    }
+
+    #[test]
+    fn test_schema_loader_list_category() {
+        init_test_logging();
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        loader.clear_cache();
+        loader.register_schema("player", "player_leave", json!({"type": "object"}));
+        loader.register_schema("player", "player_join", json!({"type": "object"}));
+        loader.register_schema("inventory", "item", json!({"type": "object"}));
+
+        assert_eq!(
+            loader.list_category("player"),
+            vec!["player_join", "player_leave"]
+        );
+        assert_eq!(loader.list_category("inventory"), vec!["item"]);
+        assert!(loader.list_category("unknown").is_empty());
+    }
+
+    #[test]
+    fn test_validation_error_codes() {
+        init_test_logging();
+        let validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "name"],
+            "properties": {
+                "id": {"type": "integer"},
+                "status": {"type": "string", "enum": ["open", "closed"]},
+                "code": {"type": "string", "pattern": "^[A-Z]+$"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        });
+        let data = json!({"id": "7", "status": "lost", "code": "abc", "tags": [1]});
+
+        let result = validator.validate_data(&data, &schema);
+        let details = result.get_error_details();
+        let codes: Vec<&str> = details.iter().map(|error| error.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                ValidationError::REQUIRED_MISSING,
+                ValidationError::PATTERN_MISMATCH,
+                ValidationError::TYPE_MISMATCH,
+                ValidationError::ENUM_MISMATCH,
+                ValidationError::TYPE_MISMATCH,
+            ]
+        );
+        let messages: Vec<String> = details.iter().map(|error| error.to_string()).collect();
+        assert_eq!(messages, result.get_errors());
+
+        let compiled = validator.validate_compiled(&data, &validator.compile(&schema).unwrap());
+        assert_eq!(compiled.get_error_details(), details);
+
+        let serialized = serde_json::to_value(&result).unwrap();
+        assert_eq!(serialized["error_codes"][0], json!("REQUIRED_MISSING"));
+        let deserialized: ValidationResult = serde_json::from_value(serialized).unwrap();
+        assert!(deserialized.error_codes.is_empty());
+        assert_eq!(
+            deserialized.get_error_details()[0].code,
+            ValidationError::UNCLASSIFIED
+        );
+
+        let header = Header::new(String::new(), "player".to_string(), String::new());
+        assert_eq!(
+            validator.validate_header(&header).get_error_details(),
+            vec![
+                ValidationError::new(
+                    ValidationError::HEADER_FIELD_MISSING,
+                    "Schema name is required in header".to_string()
+                ),
+                ValidationError::new(
+                    ValidationError::HEADER_FIELD_MISSING,
+                    "Schema version is required in header".to_string()
+                ),
+            ]
+        );
+
+        let mut capped = Validator::standalone();
+        capped.set_max_errors(1);
+        let codes: Vec<&str> = capped
+            .validate_data(&data, &schema)
+            .get_error_details()
+            .iter()
+            .map(|error| error.code)
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ValidationError::REQUIRED_MISSING,
+                ValidationError::ERRORS_OMITTED
+            ]
+        );
+
+        assert!(ValidationResult::success().get_error_details().is_empty());
+        assert_eq!(
+            ValidationResult::failure(vec!["External error".to_string()]).get_error_details()[0]
+                .code,
+            ValidationError::UNCLASSIFIED
+        );
+    }
+
+    #[test]
+    fn test_pacts_service_validate_in_range() {
+        init_test_logging();
+        struct VersionedSource;
+
+        impl SchemaSource for VersionedSource {
+            fn fetch(
+                &self,
+                _domain: &str,
+                version: &str,
+                category: &str,
+                name: &str,
+            ) -> Result<Option<serde_json::Value>, SchemaLoadError> {
+                match (version, category, name) {
+                    ("v2" | "v3" | "v5", "player", "player_join") => Ok(Some(json!({
+                        "type": "object",
+                        "required": ["player"]
+                    }))),
+                    _ => Ok(None),
+                }
+            }
+        }
+
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service
+            .schema_loader()
+            .lock()
+            .unwrap()
+            .set_schema_source(Box::new(VersionedSource));
+        let envelope = |version: &str, data: serde_json::Value| {
+            Envelope::new(
+                Header::new(
+                    version.to_string(),
+                    "player".to_string(),
+                    "player_join".to_string(),
+                ),
+                data,
+            )
+        };
+
+        let result = service.validate_in_range(&envelope("v3", json!({"player": "bee"})), 2, 4);
+        assert!(result.is_valid());
+        assert_eq!(result.get_schema_ref(), Some("bees/v3/player/player_join"));
+
+        assert_eq!(
+            service
+                .validate_in_range(&envelope("v2", json!({})), 2, 4)
+                .get_errors(),
+            ["Required field missing: player"]
+        );
+        assert_eq!(
+            service
+                .validate_in_range(&envelope("v5", json!({"player": "bee"})), 2, 4)
+                .get_errors(),
+            ["Version 5 outside accepted range 2..=4"]
+        );
+        assert_eq!(
+            service
+                .validate_in_range(&envelope("latest", json!({"player": "bee"})), 2, 4)
+                .get_errors(),
+            ["Invalid schema version: latest"]
+        );
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn test_pacts_service_validate_by_url_rejects_redirects() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        init_test_logging();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            // Serves a single redirect; following it would issue a second request
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 302 Found\r\nLocation: http://169.254.169.254/latest/meta-data\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            )
+            .unwrap();
+        });
+
+        let mut service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        service.set_allowed_schema_hosts(vec!["LOCALHOST".to_string()]);
+
+        let url = format!("http://localhost:{}/schemas/hive.json", port);
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("schema_url".to_string(), json!(url));
+        let envelope = Envelope::with_metadata(
+            Header::new("v1".to_string(), "hive".to_string(), "hive".to_string()),
+            json!({}),
+            metadata,
+        );
+
+        let result = service.validate_by_url(&envelope, "schema_url");
+        server.join().unwrap();
+        assert_eq!(
+            vec![format!("Schema URL redirects are not followed: {}", url)],
+            result.get_errors()
+        );
+    }
+
+    #[test]
+    fn test_pacts_service_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PactsService>();
+    }
+
+    #[test]
+    fn test_pacts_service_validate_data_unknown_schema() {
+        init_test_logging();
+        let service =
+            PactsService::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+
+        for result in [
+            service.validate_data(&json!({}), "hive", "no_such_schema"),
+            service.validate_bytes(b"{}", "hive", "no_such_schema"),
+            service.validate_value_of(&json!({}), "hive", "no_such_schema"),
+        ] {
+            assert!(!result.is_valid());
+            assert_eq!(
+                result.get_error_details()[0].code,
+                ValidationError::SCHEMA_UNAVAILABLE
+            );
+            assert_eq!(
+                result.get_errors(),
+                ["Schema not found: bees/v1/hive/no_such_schema"]
+            );
+        }
+
+        assert!(!service.schema_loader().is_poisoned());
+    }
+
+    #[test]
+    fn test_validator_compile_patterns() {
+        init_test_logging();
+        let validator = Validator::standalone();
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "code": {"type": "string", "pattern": "^[A-Z]+$"},
+                "tags": {"type": "array", "items": {"type": "string", "pattern": "^#"}}
+            }
+        });
+        let data = json!({"code": "abc", "tags": ["#ok", "bad"]});
+
+        let compiled = validator.compile(&schema).unwrap();
+        assert_eq!(0, validator.regex_cache_len());
+        assert_eq!(
+            validator
+                .validate_compiled(&data, &compiled)
+                .get_error_details(),
+            validator.validate_data(&data, &schema).get_error_details()
+        );
+
+        let invalid = json!({
+            "properties": {"tags": {"type": "array", "items": {"pattern": "(unclosed"}}}
+        });
+        assert_eq!(
+            validator.compile(&invalid).unwrap_err(),
+            ValidationError::new(
+                ValidationError::PATTERN_INVALID,
+                "Invalid pattern for field 'tags': (unclosed".to_string()
+            )
+        );
+
+        let stream: Vec<_> = validator
+            .validate_stream("[1, 2]".as_bytes(), &json!({"pattern": "("}))
+            .collect();
+        assert_eq!(stream.len(), 1);
+        assert_eq!(stream[0].1.get_errors(), ["Invalid pattern: ("]);
+    }
+
+    #[test]
+    fn test_validation_result_with_error() {
+        let result = ValidationResult::failure(vec!["External error".to_string()])
+            .with_schema_ref("bees/v1/hive/hive".to_string())
+            .with_warning("Schema is deprecated".to_string())
+            .with_error(ValidationError::new(
+                ValidationError::OUT_OF_ORDER,
+                "Envelope out of order".to_string(),
+            ));
+
+        assert!(!result.is_valid());
+        assert_eq!(result.get_schema_ref(), Some("bees/v1/hive/hive"));
+        assert_eq!(result.get_warnings(), ["Schema is deprecated"]);
+        assert_eq!(
+            result
+                .get_error_details()
+                .iter()
+                .map(|error| error.code)
+                .collect::<Vec<_>>(),
+            vec![ValidationError::UNCLASSIFIED, ValidationError::OUT_OF_ORDER]
+        );
+        assert!(!ValidationResult::success()
+            .with_error(ValidationError::new(
+                ValidationError::OUT_OF_ORDER,
+                "Envelope out of order".to_string()
+            ))
+            .is_valid());
+    }
+
+    #[test]
+    fn test_schema_loader_caches_extended_schemas() {
+        init_test_logging();
+        let mut loader =
+            SchemaLoader::new("schemas".to_string(), "bees".to_string(), "v1".to_string());
+        loader.register_schema("events", "base", json!({"required": ["event_id"]}));
+        loader.register_schema(
+            "events",
+            "player_join",
+            json!({"extends": "events/base", "required": ["player"]}),
+        );
+
+        let first = loader.load_schema("events", "player_join");
+        let second = loader.load_schema("events", "player_join");
+        assert!(std::sync::Arc::ptr_eq(&first, &second));
+        assert_eq!(first["required"], json!(["event_id", "player"]));
+
+        loader.register_schema(
+            "events",
+            "base",
+            json!({"required": ["event_id", "timestamp"]}),
+        );
+        let rebased = loader.load_schema("events", "player_join");
+        assert!(!std::sync::Arc::ptr_eq(&first, &rebased));
+        assert_eq!(
+            rebased["required"],
+            json!(["event_id", "timestamp", "player"])
+        );
+
+        loader.clear_cache();
+        assert!(loader
+            .load_schema_version("v1", "events", "player_join")
+            .is_err());
+    }
 }
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Manifest of the schemas available to a service
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SchemaCatalog {
+    #[serde(rename = "domain")]
+    pub domain: String,
+
+    #[serde(rename = "entries")]
+    pub entries: Vec<SchemaCatalogEntry>,
+}
+
+/// A single schema listed in a [`SchemaCatalog`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SchemaCatalogEntry {
+    #[serde(rename = "category")]
+    pub category: String,
+
+    #[serde(rename = "name")]
+    pub name: String,
+
+    #[serde(rename = "version")]
+    pub version: String,
+
+    #[serde(rename = "digest")]
+    pub digest: String,
+
+    #[serde(rename = "title")]
+    pub title: Option<String>,
+}
+
+impl SchemaCatalog {
+    /// Finds the entry for a schema, if listed
+    pub fn find(&self, version: &str, category: &str, name: &str) -> Option<&SchemaCatalogEntry> {
+        self.entries.iter().find(|entry| {
+            entry.version == version && entry.category == category && entry.name == name
+        })
+    }
+}
@@ -1,9 +1,76 @@
 use crate::model::header::Header;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, Write};
+
+const REDACTED_VALUE: &str = "***";
+
+/// Metadata keys redacted by [`Envelope::redacted`]
+pub const DEFAULT_SENSITIVE_METADATA_KEYS: [&str; 3] = ["auth_token", "_signature", "password"];
+
+/// Substrings marking a data key as sensitive for [`Envelope::redacted`]
+const SENSITIVE_DATA_KEY_PARTS: [&str; 2] = ["password", "secret"];
+
+/// Error yielded by [`Envelope::read_ndjson`] for a line that could not be used
+#[derive(Debug)]
+pub enum ReadEnvelopeError {
+    /// The line could not be read from the underlying reader
+    Io { line: usize, source: std::io::Error },
+    /// The line is not a valid JSON envelope
+    InvalidEnvelope {
+        line: usize,
+        source: serde_json::Error,
+    },
+}
+
+impl ReadEnvelopeError {
+    /// The 1-based line number the error occurred on
+    pub fn line(&self) -> usize {
+        match self {
+            ReadEnvelopeError::Io { line, .. }
+            | ReadEnvelopeError::InvalidEnvelope { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for ReadEnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadEnvelopeError::Io { line, source } => {
+                write!(f, "Failed to read line {}: {}", line, source)
+            }
+            ReadEnvelopeError::InvalidEnvelope { line, source } => {
+                write!(f, "Invalid envelope on line {}: {}", line, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReadEnvelopeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadEnvelopeError::Io { source, .. } => Some(source),
+            ReadEnvelopeError::InvalidEnvelope { source, .. } => Some(source),
+        }
+    }
+}
+
+/// A single difference between two envelopes' data, addressed by JSON pointer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    #[serde(rename = "path")]
+    pub path: String,
+
+    #[serde(rename = "old")]
+    pub old: Option<serde_json::Value>,
+
+    #[serde(rename = "new")]
+    pub new: Option<serde_json::Value>,
+}
 
 /// Envelope struct that wraps data with metadata for schema validation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Envelope {
     #[serde(rename = "header")]
     pub header: Header,
@@ -11,7 +78,7 @@ pub struct Envelope {
     #[serde(rename = "data")]
     pub data: serde_json::Value,
 
-    #[serde(rename = "metadata")]
+    #[serde(rename = "metadata", default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
@@ -38,6 +105,84 @@ impl Envelope {
         }
     }
 
+    /// Creates an envelope from its header, data, and optional metadata
+    pub fn from_parts(
+        header: Header,
+        data: serde_json::Value,
+        metadata: Option<HashMap<String, serde_json::Value>>,
+    ) -> Self {
+        Self {
+            header,
+            data,
+            metadata,
+        }
+    }
+
+    /// Creates an envelope by deserializing a raw body according to the header's
+    /// content type (`application/json`, `application/yaml`, or
+    /// `application/msgpack`). A header without a content type is treated as JSON
+    pub fn from_body(header: Header, body: &[u8]) -> Result<Self, String> {
+        let content_type = header
+            .content_type()
+            .unwrap_or("application/json")
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        let data = match content_type.as_str() {
+            "application/json" => serde_json::from_slice(body).map_err(|e| e.to_string()),
+            "application/yaml" | "application/x-yaml" => {
+                serde_yaml::from_slice(body).map_err(|e| e.to_string())
+            }
+            "application/msgpack" | "application/x-msgpack" => {
+                rmp_serde::from_slice(body).map_err(|e| e.to_string())
+            }
+            _ => return Err(format!("Unsupported content type: {}", content_type)),
+        }
+        .map_err(|e| format!("Invalid {} body: {}", content_type, e))?;
+
+        Ok(Self::new(header, data))
+    }
+
+    /// Parses a document holding either a single envelope or a top-level array of
+    /// envelopes
+    pub fn parse_many(s: &str) -> Result<Vec<Envelope>, serde_json::Error> {
+        match serde_json::from_str::<serde_json::Value>(s)? {
+            serde_json::Value::Array(items) => {
+                items.into_iter().map(serde_json::from_value).collect()
+            }
+            single => Ok(vec![serde_json::from_value(single)?]),
+        }
+    }
+
+    /// Replaces the data with the result of `f`, keeping the header and metadata
+    pub fn map_data<F: FnOnce(serde_json::Value) -> serde_json::Value>(mut self, f: F) -> Envelope {
+        self.data = f(self.data);
+        self
+    }
+
+    /// Replaces the data with the result of `f`, keeping the header and metadata,
+    /// or returns the error from `f`
+    pub fn try_map_data<E, F: FnOnce(serde_json::Value) -> Result<serde_json::Value, E>>(
+        mut self,
+        f: F,
+    ) -> Result<Envelope, E> {
+        self.data = f(self.data)?;
+        Ok(self)
+    }
+
+    /// Gets the `(version, category, name)` of the schema the envelope claims,
+    /// as recorded in its header
+    pub fn schema_ref(&self) -> (String, String, String) {
+        (
+            self.header.schema_version.clone(),
+            self.header.schema_category.clone(),
+            self.header.schema_name.clone(),
+        )
+    }
+
     /// Gets the header
     pub fn header(&self) -> &Header {
         &self.header
@@ -52,4 +197,167 @@ impl Envelope {
     pub fn metadata(&self) -> Option<&HashMap<String, serde_json::Value>> {
         self.metadata.as_ref()
     }
+
+    /// Creates a copy safe for logging, replacing data properties marked
+    /// `writeOnly` in the schema and any `auth_token` metadata with `***`
+    pub fn redacted_by_schema(&self, schema: &serde_json::Value) -> Envelope {
+        let mut redacted = self.clone();
+        redact_write_only(&mut redacted.data, schema);
+        redacted.redact_metadata(&["auth_token"]);
+        redacted
+    }
+
+    /// Creates a copy safe for logging, replacing the default sensitive metadata
+    /// keys and any data values under keys containing `password` or `secret` with `***`
+    pub fn redacted(&self) -> Envelope {
+        self.redacted_with_keys(&DEFAULT_SENSITIVE_METADATA_KEYS)
+    }
+
+    /// Like [`Envelope::redacted`], but redacts the given metadata keys instead
+    /// of the defaults
+    pub fn redacted_with_keys(&self, metadata_keys: &[&str]) -> Envelope {
+        let mut redacted = self.clone();
+        redact_sensitive_keys(&mut redacted.data);
+        redacted.redact_metadata(metadata_keys);
+        redacted
+    }
+
+    fn redact_metadata(&mut self, keys: &[&str]) {
+        if let Some(metadata) = self.metadata.as_mut() {
+            for key in keys {
+                if let Some(value) = metadata.get_mut(*key) {
+                    *value = serde_json::Value::from(REDACTED_VALUE);
+                }
+            }
+        }
+    }
+
+    /// Lists the added, removed, and changed leaves between this envelope's data
+    /// and another's, recursing into objects
+    pub fn diff(&self, other: &Envelope) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        diff_values("", &self.data, &other.data, &mut changes);
+        changes
+    }
+
+    /// Lazily reads newline-delimited JSON envelopes, one per line. Blank lines are
+    /// skipped, and a line that cannot be read or parsed yields an error tagged with
+    /// its line number without ending the stream
+    pub fn read_ndjson<R: BufRead>(
+        reader: R,
+    ) -> impl Iterator<Item = Result<Envelope, ReadEnvelopeError>> {
+        reader.lines().enumerate().filter_map(|(index, line)| {
+            let line_number = index + 1;
+            match line {
+                Ok(line) if line.trim().is_empty() => None,
+                Ok(line) => Some(serde_json::from_str(&line).map_err(|source| {
+                    ReadEnvelopeError::InvalidEnvelope {
+                        line: line_number,
+                        source,
+                    }
+                })),
+                Err(source) => Some(Err(ReadEnvelopeError::Io {
+                    line: line_number,
+                    source,
+                })),
+            }
+        })
+    }
+
+    /// Writes envelopes as newline-delimited JSON, one compact envelope per line,
+    /// and flushes the writer once all lines are written
+    pub fn write_ndjson<W: Write>(envelopes: &[Envelope], writer: W) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(writer);
+        for envelope in envelopes {
+            serde_json::to_writer(&mut writer, envelope)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+}
+
+impl From<Header> for Envelope {
+    /// Creates an envelope with null data and no metadata
+    fn from(header: Header) -> Self {
+        Self::new(header, serde_json::Value::Null)
+    }
+}
+
+fn diff_values(
+    path: &str,
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (old.as_object(), new.as_object()) {
+        (Some(old_object), Some(new_object)) => {
+            for (key, old_value) in old_object {
+                let child_path = format!("{}/{}", path, escape_pointer_token(key));
+                match new_object.get(key) {
+                    Some(new_value) => diff_values(&child_path, old_value, new_value, changes),
+                    None => changes.push(FieldChange {
+                        path: child_path,
+                        old: Some(old_value.clone()),
+                        new: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new_object {
+                if !old_object.contains_key(key) {
+                    changes.push(FieldChange {
+                        path: format!("{}/{}", path, escape_pointer_token(key)),
+                        old: None,
+                        new: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        _ if old != new => changes.push(FieldChange {
+            path: path.to_string(),
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn redact_sensitive_keys(data: &mut serde_json::Value) {
+    match data {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                let key = key.to_lowercase();
+                if SENSITIVE_DATA_KEY_PARTS
+                    .iter()
+                    .any(|part| key.contains(part))
+                {
+                    *value = serde_json::Value::from(REDACTED_VALUE);
+                } else {
+                    redact_sensitive_keys(value);
+                }
+            }
+        }
+        serde_json::Value::Array(elements) => elements.iter_mut().for_each(redact_sensitive_keys),
+        _ => {}
+    }
+}
+
+fn redact_write_only(data: &mut serde_json::Value, schema: &serde_json::Value) {
+    if let (Some(object), Some(properties)) = (
+        data.as_object_mut(),
+        schema.get("properties").and_then(|p| p.as_object()),
+    ) {
+        for (property_name, property_schema) in properties {
+            if let Some(value) = object.get_mut(property_name) {
+                if property_schema.get("writeOnly").and_then(|w| w.as_bool()) == Some(true) {
+                    *value = serde_json::Value::from(REDACTED_VALUE);
+                } else {
+                    redact_write_only(value, property_schema);
+                }
+            }
+        }
+    }
 }
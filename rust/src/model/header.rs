@@ -1,8 +1,67 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, DurationRound, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Serde helper that serializes a timestamp as RFC3339 truncated to milliseconds,
+/// e.g. `2025-01-01T00:00:00.123Z`, for use with `#[serde(with = "...")]`
+pub mod millis_timestamp {
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serializes the timestamp with exactly three fractional digits
+    pub fn serialize<S: Serializer>(
+        timestamp: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&timestamp.to_rfc3339_opts(SecondsFormat::Millis, true))
+    }
+
+    /// Deserializes an RFC3339 timestamp, truncating it to milliseconds
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        let timestamp = DateTime::parse_from_rfc3339(&raw)
+            .map_err(serde::de::Error::custom)?
+            .with_timezone(&Utc);
+        Ok(super::truncate_to_millis(timestamp))
+    }
+}
+
+/// Error returned when a compact `version/category/name` reference cannot be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseHeaderError {
+    /// The reference did not have exactly three `/`-separated segments
+    WrongSegmentCount { reference: String, found: usize },
+    /// One of the segments was empty
+    EmptySegment {
+        reference: String,
+        segment: &'static str,
+    },
+}
+
+impl fmt::Display for ParseHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseHeaderError::WrongSegmentCount { reference, found } => write!(
+                f,
+                "Invalid schema reference '{}': expected version/category/name, found {} segment(s)",
+                reference, found
+            ),
+            ParseHeaderError::EmptySegment { reference, segment } => write!(
+                f,
+                "Invalid schema reference '{}': {} is empty",
+                reference, segment
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseHeaderError {}
 
 /// Header struct that contains metadata about the envelope
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Header {
     #[serde(rename = "schema_version")]
     pub schema_version: String,
@@ -16,8 +75,19 @@ pub struct Header {
     #[serde(rename = "timestamp")]
     pub timestamp: DateTime<Utc>,
 
-    #[serde(rename = "content_type")]
+    #[serde(
+        rename = "content_type",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub content_type: Option<String>,
+
+    #[serde(
+        rename = "expires_at",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl Header {
@@ -29,6 +99,7 @@ impl Header {
             schema_name,
             timestamp: Utc::now(),
             content_type: None,
+            expires_at: None,
         }
     }
 
@@ -45,9 +116,54 @@ impl Header {
             schema_name,
             timestamp: Utc::now(),
             content_type: Some(content_type),
+            expires_at: None,
         }
     }
 
+    /// Creates a new header from a compact `version/category/name` reference,
+    /// e.g. `v1/player/join`
+    pub fn from_ref(reference: &str) -> Result<Self, ParseHeaderError> {
+        let segments: Vec<&str> = reference.split('/').collect();
+        let (version, category, name) = match segments.as_slice() {
+            [version, category, name] => (*version, *category, *name),
+            _ => {
+                return Err(ParseHeaderError::WrongSegmentCount {
+                    reference: reference.to_string(),
+                    found: segments.len(),
+                })
+            }
+        };
+
+        for (segment, value) in [("version", version), ("category", category), ("name", name)] {
+            if value.is_empty() {
+                return Err(ParseHeaderError::EmptySegment {
+                    reference: reference.to_string(),
+                    segment,
+                });
+            }
+        }
+
+        Ok(Self::new(
+            version.to_string(),
+            category.to_string(),
+            name.to_string(),
+        ))
+    }
+
+    /// Sets the expiry to the header timestamp plus the given time-to-live
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.expires_at = Some(self.timestamp + ttl);
+        self
+    }
+
+    /// Truncates the timestamp and expiry to millisecond precision so the header
+    /// serializes to the same bytes after a serialize/deserialize round-trip
+    pub fn with_millisecond_timestamp(mut self) -> Self {
+        self.timestamp = truncate_to_millis(self.timestamp);
+        self.expires_at = self.expires_at.map(truncate_to_millis);
+        self
+    }
+
     /// Gets the schema version
     pub fn schema_version(&self) -> &str {
         &self.schema_version
@@ -72,4 +188,23 @@ impl Header {
     pub fn content_type(&self) -> Option<&str> {
         self.content_type.as_deref()
     }
+
+    /// Gets the expiry time
+    pub fn expires_at(&self) -> Option<&DateTime<Utc>> {
+        self.expires_at.as_ref()
+    }
+}
+
+impl FromStr for Header {
+    type Err = ParseHeaderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Header::from_ref(s)
+    }
+}
+
+fn truncate_to_millis(timestamp: DateTime<Utc>) -> DateTime<Utc> {
+    timestamp
+        .duration_trunc(Duration::milliseconds(1))
+        .unwrap_or(timestamp)
 }
@@ -1,5 +1,9 @@
+pub mod catalog;
 pub mod envelope;
 pub mod header;
+pub mod typed_envelope;
 
-pub use envelope::Envelope;
-pub use header::Header;
+pub use catalog::{SchemaCatalog, SchemaCatalogEntry};
+pub use envelope::{Envelope, FieldChange, ReadEnvelopeError, DEFAULT_SENSITIVE_METADATA_KEYS};
+pub use header::{millis_timestamp, Header, ParseHeaderError};
+pub use typed_envelope::TypedEnvelope;
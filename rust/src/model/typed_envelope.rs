@@ -0,0 +1,68 @@
+use crate::model::envelope::Envelope;
+use crate::model::header::Header;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Envelope whose data is a strongly-typed payload instead of a JSON value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TypedEnvelope<T> {
+    #[serde(rename = "header")]
+    pub header: Header,
+
+    #[serde(rename = "data")]
+    pub data: T,
+
+    #[serde(rename = "metadata", default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+}
+
+impl<T> TypedEnvelope<T> {
+    /// Creates a new typed envelope with header and data
+    pub fn new(header: Header, data: T) -> Self {
+        Self {
+            header,
+            data,
+            metadata: None,
+        }
+    }
+
+    /// Gets the header
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Gets the data
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    /// Gets the metadata
+    pub fn metadata(&self) -> Option<&HashMap<String, serde_json::Value>> {
+        self.metadata.as_ref()
+    }
+}
+
+impl<T: DeserializeOwned> TypedEnvelope<T> {
+    /// Creates a typed envelope by deserializing the envelope's data, keeping the
+    /// header and metadata
+    pub fn from_envelope(envelope: Envelope) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            header: envelope.header,
+            data: serde_json::from_value(envelope.data)?,
+            metadata: envelope.metadata,
+        })
+    }
+}
+
+impl<T: Serialize> TypedEnvelope<T> {
+    /// Converts back to an untyped envelope by serializing the data, keeping the
+    /// header and metadata
+    pub fn into_envelope(self) -> Result<Envelope, serde_json::Error> {
+        Ok(Envelope {
+            header: self.header,
+            data: serde_json::to_value(self.data)?,
+            metadata: self.metadata,
+        })
+    }
+}